@@ -0,0 +1,210 @@
+//! Classify what a Claude Code agent is doing from a capture of its pane
+//! (`tmux capture-pane` without escapes), by looking for the parts of its UI
+//! that are on screen.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AgentState {
+    /// The spinner is running: thinking, calling tools or streaming output
+    Working,
+    /// A tool call or edit waits for approval
+    Permission,
+    /// The last turn ended with an API or account error
+    Error,
+    /// The input box is empty and waiting for a message
+    Idle,
+    /// No agent UI on screen (starting up, exited, or another program)
+    Unknown,
+}
+
+/// The line that decided the state.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Evidence {
+    /// Name of the pattern that matched
+    pub rule: &'static str,
+    /// 1-based line of the capture
+    pub line: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Detection {
+    pub state: AgentState,
+    pub evidence: Option<Evidence>,
+}
+
+struct Patterns {
+    permission_question: Regex,
+    first_choice: Regex,
+    spinner: Regex,
+    error_banner: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| Patterns {
+        permission_question: Regex::new(
+            r"^Do you want to (proceed|make this edit|create|allow|run)",
+        )
+        .unwrap(),
+        first_choice: Regex::new(r"^❯\s*1\.\s*Yes").unwrap(),
+        // Glyphs cycle through the set; the verb and timer change every tick
+        spinner: Regex::new(r"^[·✢✳✶✻✽*]\s+\S+…\s*\(.*esc to interrupt").unwrap(),
+        error_banner: Regex::new(
+            r"API Error|usage limit reached|Credit balance is too low|Invalid API key|Please run /login",
+        )
+        .unwrap(),
+    })
+}
+
+/// Lines above the input box searched for an error banner.
+const ERROR_WINDOW: usize = 3;
+
+/// A line with box borders (`│`) and the tool-result gutter (`⎿`) removed.
+fn content(line: &str) -> &str {
+    let line = line.trim();
+    let line = line.strip_prefix('│').unwrap_or(line);
+    let line = line.strip_suffix('│').unwrap_or(line).trim();
+    line.strip_prefix('⎿').map(str::trim).unwrap_or(line)
+}
+
+fn is_border(line: &str) -> bool {
+    let line = line.trim();
+    let body = line
+        .strip_prefix(['╭', '╰'])
+        .map(|l| l.trim_end_matches(['╮', '╯']))
+        .unwrap_or(line);
+    body.chars().count() >= 10 && body.chars().all(|c| c == '─')
+}
+
+/// Index of the prompt line of the input box at the bottom of the screen: the
+/// content of the last bordered block, which starts with `>`.
+fn input_box(lines: &[&str]) -> Option<usize> {
+    let bottom = lines.iter().rposition(|l| is_border(l))?;
+    let top = lines[..bottom].iter().rposition(|l| is_border(l))?;
+    let prompt = top + 1;
+    (prompt < bottom && (content(lines[prompt]) == ">" || content(lines[prompt]).starts_with("> ")))
+        .then_some(prompt)
+}
+
+fn evidence(rule: &'static str, lines: &[&str], index: usize) -> Evidence {
+    Evidence {
+        rule,
+        line: index + 1,
+        text: lines[index].trim().to_string(),
+    }
+}
+
+pub fn detect(screen: &str) -> Detection {
+    let patterns = patterns();
+    let lines: Vec<&str> = screen.lines().collect();
+    let found = |state, evidence| Detection {
+        state,
+        evidence: Some(evidence),
+    };
+
+    if let Some(question) = lines
+        .iter()
+        .position(|l| patterns.permission_question.is_match(content(l)))
+    {
+        if lines[question..]
+            .iter()
+            .any(|l| patterns.first_choice.is_match(content(l)))
+        {
+            return found(
+                AgentState::Permission,
+                evidence("permission-prompt", &lines, question),
+            );
+        }
+    }
+
+    if let Some(spinner) = lines
+        .iter()
+        .rposition(|l| patterns.spinner.is_match(content(l)))
+    {
+        return found(AgentState::Working, evidence("spinner", &lines, spinner));
+    }
+
+    if let Some(prompt) = input_box(&lines) {
+        // The box's top border sits right above the prompt
+        let error = lines[..prompt - 1]
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, l)| !l.trim().is_empty())
+            .take(ERROR_WINDOW)
+            .find(|(_, l)| patterns.error_banner.is_match(l));
+        if let Some((index, _)) = error {
+            return found(AgentState::Error, evidence("error-banner", &lines, index));
+        }
+        return found(AgentState::Idle, evidence("input-box", &lines, prompt));
+    }
+
+    Detection {
+        state: AgentState::Unknown,
+        evidence: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        let path = format!("{}/tests/fixtures/panes/{name}", env!("CARGO_MANIFEST_DIR"));
+        std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{path}: {e}"))
+    }
+
+    fn assert_detects(name: &str, state: AgentState, rule: Option<&str>) {
+        let detection = detect(&fixture(name));
+        assert_eq!(detection.state, state, "{name}: {detection:?}");
+        assert_eq!(detection.evidence.as_ref().map(|e| e.rule), rule, "{name}");
+    }
+
+    #[test]
+    fn spinner_means_working_even_with_the_input_box_shown() {
+        assert_detects("working.txt", AgentState::Working, Some("spinner"));
+    }
+
+    #[test]
+    fn permission_prompts() {
+        assert_detects(
+            "permission.txt",
+            AgentState::Permission,
+            Some("permission-prompt"),
+        );
+        assert_detects(
+            "edit-permission.txt",
+            AgentState::Permission,
+            Some("permission-prompt"),
+        );
+    }
+
+    #[test]
+    fn empty_input_box_is_idle() {
+        let detection = detect(&fixture("idle.txt"));
+        assert_eq!(detection.state, AgentState::Idle);
+        // Earlier messages echoed with `>` are not the input box
+        assert_eq!(detection.evidence.unwrap().line, 14);
+        assert_detects("idle-rule.txt", AgentState::Idle, Some("input-box"));
+    }
+
+    #[test]
+    fn error_banner_above_the_input_box() {
+        let detection = detect(&fixture("error.txt"));
+        assert_eq!(detection.state, AgentState::Error);
+        assert!(detection.evidence.unwrap().text.contains("API Error: 529"));
+    }
+
+    #[test]
+    fn no_agent_ui_is_unknown() {
+        assert_detects("shell.txt", AgentState::Unknown, None);
+        assert_eq!(detect("").state, AgentState::Unknown);
+    }
+}
@@ -0,0 +1,40 @@
+//! `directiv --agent-hook <worktree>`
+//!
+//! The command Claude Code runs for the hooks `install_agent_hooks` writes into a
+//! worktree. It forwards the event JSON from stdin to the app's socket and exits
+//! quietly, whether or not the app is running, so it never holds up the agent.
+
+use crate::commands::agent_hooks::agent_socket_path;
+use std::io::Read;
+
+/// Run the relay; `args` are the arguments following `--agent-hook`. Returns the
+/// exit code, always 0.
+pub fn run(args: &[String]) -> i32 {
+    let Some(worktree) = args.first() else {
+        return 0;
+    };
+    let mut input = String::new();
+    if std::io::stdin().read_to_string(&mut input).is_err() {
+        return 0;
+    }
+    let Ok(payload) = serde_json::from_str::<serde_json::Value>(&input) else {
+        return 0;
+    };
+    let message = serde_json::json!({ "worktree": worktree, "payload": payload });
+
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::net::UnixStream;
+        use std::time::Duration;
+
+        let Ok(path) = agent_socket_path() else {
+            return 0;
+        };
+        if let Ok(mut stream) = UnixStream::connect(path) {
+            let _ = stream.set_write_timeout(Some(Duration::from_secs(1)));
+            let _ = writeln!(stream, "{message}");
+        }
+    }
+    0
+}
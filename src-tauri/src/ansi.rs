@@ -0,0 +1,274 @@
+//! Convert terminal output with SGR escape sequences (as produced by
+//! `tmux capture-pane -e`) into styled spans the frontend can render.
+
+use serde::Serialize;
+
+/// Colors 0–15 stay palette indices so the frontend can map them to its theme;
+/// the 256-color cube, grays and true colors are resolved to `#rrggbb`.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Color {
+    Palette(u8),
+    Rgb(String),
+}
+
+#[derive(Debug, Serialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub inverse: bool,
+    pub strikethrough: bool,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Span {
+    pub text: String,
+    #[serde(flatten)]
+    pub style: Style,
+}
+
+fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color::Rgb(format!("#{r:02x}{g:02x}{b:02x}"))
+}
+
+fn indexed(n: u8) -> Color {
+    match n {
+        0..=15 => Color::Palette(n),
+        16..=231 => {
+            const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+            let n = n - 16;
+            rgb(
+                LEVELS[usize::from(n / 36)],
+                LEVELS[usize::from(n / 6 % 6)],
+                LEVELS[usize::from(n % 6)],
+            )
+        }
+        _ => {
+            let level = 8 + (n - 232) * 10;
+            rgb(level, level, level)
+        }
+    }
+}
+
+/// Parse an extended color (`5;n` or `2;r;g;b`) from `args`, returning it and the
+/// number of arguments used.
+fn extended_color(args: &[u16]) -> Option<(Color, usize)> {
+    match args {
+        [5, n, ..] => Some((indexed(*n as u8), 2)),
+        [2, r, g, b, ..] => Some((rgb(*r as u8, *g as u8, *b as u8), 4)),
+        _ => None,
+    }
+}
+
+fn apply_sgr(style: &mut Style, params: &str) {
+    let params: Vec<&str> = params.split(';').collect();
+    let mut i = 0;
+    while i < params.len() {
+        // Colon sub-parameters (e.g. `38:2::r:g:b`, `4:3`) live inside one parameter
+        let sub: Vec<u16> = params[i]
+            .split(':')
+            .map(|s| s.parse().unwrap_or(0))
+            .collect();
+        let code = sub.first().copied().unwrap_or(0);
+        i += 1;
+
+        match code {
+            0 => *style = Style::default(),
+            1 => style.bold = true,
+            2 => style.dim = true,
+            3 => style.italic = true,
+            4 => style.underline = sub.get(1) != Some(&0),
+            7 => style.inverse = true,
+            9 => style.strikethrough = true,
+            22 => {
+                style.bold = false;
+                style.dim = false;
+            }
+            23 => style.italic = false,
+            24 => style.underline = false,
+            27 => style.inverse = false,
+            29 => style.strikethrough = false,
+            30..=37 => style.fg = Some(Color::Palette((code - 30) as u8)),
+            39 => style.fg = None,
+            40..=47 => style.bg = Some(Color::Palette((code - 40) as u8)),
+            49 => style.bg = None,
+            90..=97 => style.fg = Some(Color::Palette((code - 90 + 8) as u8)),
+            100..=107 => style.bg = Some(Color::Palette((code - 100 + 8) as u8)),
+            38 | 48 => {
+                let color = if sub.len() > 1 {
+                    // `38:2:<colorspace>:r:g:b` carries an (often empty) colorspace id
+                    let args = if sub[1] == 2 && sub.len() >= 6 {
+                        [&sub[1..2], &sub[3..]].concat()
+                    } else {
+                        sub[1..].to_vec()
+                    };
+                    extended_color(&args).map(|(color, _)| color)
+                } else {
+                    let rest: Vec<u16> = params[i..]
+                        .iter()
+                        .take(4)
+                        .map(|s| s.parse().unwrap_or(0))
+                        .collect();
+                    extended_color(&rest).map(|(color, used)| {
+                        i += used;
+                        color
+                    })
+                };
+                if code == 38 {
+                    style.fg = color;
+                } else {
+                    style.bg = color;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn push_text(line: &mut Vec<Span>, style: &Style, c: char) {
+    match line.last_mut() {
+        Some(span) if span.style == *style => span.text.push(c),
+        _ => line.push(Span {
+            text: c.to_string(),
+            style: style.clone(),
+        }),
+    }
+}
+
+/// Split `input` into lines of styled spans. Styles carry over line breaks, as they
+/// do on a terminal; escape sequences other than SGR are dropped.
+pub fn to_spans(input: &str) -> Vec<Vec<Span>> {
+    let mut lines = vec![Vec::new()];
+    let mut style = Style::default();
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI: parameters up to a final byte in @..~
+                Some('[') => {
+                    let mut params = String::new();
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            if c == 'm' {
+                                apply_sgr(&mut style, &params);
+                            }
+                            break;
+                        }
+                        params.push(c);
+                    }
+                }
+                // OSC (e.g. hyperlinks): up to BEL or ST
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\n' => lines.push(Vec::new()),
+            '\t' => push_text(lines.last_mut().unwrap(), &style, c),
+            c if c.is_control() => {}
+            c => push_text(lines.last_mut().unwrap(), &style, c),
+        }
+    }
+
+    // Output ends with a newline; don't report an empty line after it
+    if lines.len() > 1 && lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum StripState {
+    #[default]
+    Text,
+    Escape,
+    Csi,
+    Osc,
+    OscEscape,
+}
+
+/// Removes escape sequences and carriage returns from a byte stream. Sequences may
+/// be split across chunks, so the parser state is kept between calls.
+#[derive(Debug, Default)]
+pub struct Stripper {
+    state: StripState,
+}
+
+impl Stripper {
+    pub fn strip(&mut self, input: &[u8], out: &mut Vec<u8>) {
+        for &b in input {
+            self.state = match (self.state, b) {
+                (StripState::Text, 0x1b) => StripState::Escape,
+                (StripState::Text, b'\r') => StripState::Text,
+                (StripState::Text, b) => {
+                    out.push(b);
+                    StripState::Text
+                }
+                (StripState::Escape, b'[') => StripState::Csi,
+                (StripState::Escape, b']') => StripState::Osc,
+                (StripState::Escape, _) => StripState::Text,
+                (StripState::Csi, 0x40..=0x7e) => StripState::Text,
+                (StripState::Csi, _) => StripState::Csi,
+                (StripState::Osc, 0x07) => StripState::Text,
+                (StripState::Osc, 0x1b) => StripState::OscEscape,
+                (StripState::Osc, _) => StripState::Osc,
+                (StripState::OscEscape, b'\\') => StripState::Text,
+                (StripState::OscEscape, _) => StripState::Osc,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_text_is_one_unstyled_span_per_line() {
+        let lines = to_spans("hello\nworld\n");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0][0].text, "hello");
+        assert_eq!(lines[1][0].style, Style::default());
+    }
+
+    #[test]
+    fn sgr_colors_and_attributes() {
+        let lines = to_spans("\x1b[1;31merr\x1b[0m ok \x1b[38;5;196mx\x1b[48;2;1;2;3my");
+        let spans = &lines[0];
+        assert_eq!(spans[0].text, "err");
+        assert!(spans[0].style.bold);
+        assert_eq!(spans[0].style.fg, Some(Color::Palette(1)));
+        assert_eq!(spans[1].style, Style::default());
+        assert_eq!(spans[2].style.fg, Some(Color::Rgb("#ff0000".into())));
+        assert_eq!(spans[3].style.bg, Some(Color::Rgb("#010203".into())));
+    }
+
+    #[test]
+    fn colon_subparameters_and_non_sgr_sequences() {
+        let lines = to_spans("\x1b[38:2::10:20:30ma\x1b[2K\x1b]8;;http://x\x1b\\b\x07");
+        assert_eq!(lines[0].len(), 1);
+        assert_eq!(lines[0][0].text, "ab");
+        assert_eq!(lines[0][0].style.fg, Some(Color::Rgb("#0a141e".into())));
+    }
+
+    #[test]
+    fn stripper_handles_sequences_split_across_chunks() {
+        let mut stripper = Stripper::default();
+        let mut out = Vec::new();
+        stripper.strip(b"a\x1b[3", &mut out);
+        stripper.strip(b"1mb\r\n\x1b]0;ti", &mut out);
+        stripper.strip(b"tle\x07c", &mut out);
+        assert_eq!(out, b"ab\nc");
+    }
+}
@@ -0,0 +1,187 @@
+//! asciicast v2 recordings: the writer behind `directiv --pipe-log --cast`, and
+//! reading and slicing the `.cast` files it produces.
+//!
+//! A cast is a JSON header line followed by one `[seconds, "o", text]` line per
+//! chunk of output, seconds counted from the start of the recording.
+
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Writes a pane's raw output as the events of a cast.
+pub(crate) struct CastWriter {
+    file: File,
+    started: Instant,
+    /// Bytes of a UTF-8 sequence split across reads, kept for the next event
+    pending: Vec<u8>,
+    pub(crate) written: u64,
+}
+
+impl CastWriter {
+    /// Create `<dir>/<unix-millis>.cast` for a terminal of `width`×`height`.
+    pub(crate) fn create(dir: &Path, width: u16, height: u16) -> std::io::Result<Self> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(format!("{}.cast", now.as_millis())))?;
+        let header = serde_json::json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "timestamp": now.as_secs(),
+            "env": { "TERM": "xterm-256color" },
+        });
+        let line = format!("{header}\n");
+        file.write_all(line.as_bytes())?;
+        Ok(Self {
+            file,
+            started: Instant::now(),
+            pending: Vec::new(),
+            written: line.len() as u64,
+        })
+    }
+
+    pub(crate) fn write(&mut self, output: &[u8]) -> std::io::Result<()> {
+        self.pending.extend_from_slice(output);
+        let text = take_utf8(&mut self.pending);
+        if text.is_empty() {
+            return Ok(());
+        }
+        let seconds = self.started.elapsed().as_millis() as f64 / 1000.0;
+        let line = format!("{}\n", serde_json::json!([seconds, "o", text]));
+        self.file.write_all(line.as_bytes())?;
+        self.written += line.len() as u64;
+        Ok(())
+    }
+}
+
+/// Decode the complete characters of `bytes`, leaving an unfinished trailing
+/// sequence in place. Invalid bytes become U+FFFD.
+fn take_utf8(bytes: &mut Vec<u8>) -> String {
+    let mut text = String::new();
+    let mut rest = bytes.as_slice();
+    loop {
+        match std::str::from_utf8(rest) {
+            Ok(valid) => {
+                text.push_str(valid);
+                rest = &[];
+                break;
+            }
+            Err(e) => {
+                let (valid, after) = rest.split_at(e.valid_up_to());
+                text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                match e.error_len() {
+                    Some(len) => {
+                        text.push(char::REPLACEMENT_CHARACTER);
+                        rest = &after[len..];
+                    }
+                    // Unfinished: the rest arrives with the next read
+                    None => {
+                        rest = after;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    *bytes = rest.to_vec();
+    text
+}
+
+pub(crate) struct Cast {
+    pub(crate) header: Value,
+    /// `(seconds, code, data)`
+    pub(crate) events: Vec<(f64, String, String)>,
+}
+
+/// Parse a cast, skipping lines that aren't events (such as one cut short when
+/// the recording stopped).
+pub(crate) fn parse_cast(content: &str) -> Option<Cast> {
+    let mut lines = content.lines();
+    let header: Value = serde_json::from_str(lines.next()?).ok()?;
+    if header.get("version").and_then(Value::as_u64) != Some(2) {
+        return None;
+    }
+    let events = lines
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    Some(Cast { header, events })
+}
+
+/// The part of `cast` between `start` and `end` seconds, as a cast of its own.
+/// Output from before `start` is replayed at once in the first event, so the
+/// slice opens on the screen as it was at `start`.
+pub(crate) fn slice(cast: &Cast, start: f64, end: Option<f64>) -> String {
+    let start = start.max(0.0);
+    let end = end.unwrap_or(f64::INFINITY);
+    let prefix: String = cast
+        .events
+        .iter()
+        .filter(|(t, code, _)| *t < start && code == "o")
+        .map(|(_, _, data)| data.as_str())
+        .collect();
+    let events: Vec<Value> = std::iter::once((0.0, "o", prefix.as_str()))
+        .filter(|(_, _, data)| !data.is_empty())
+        .chain(
+            cast.events
+                .iter()
+                .filter(|(t, _, _)| *t >= start && *t <= end)
+                .map(|(t, code, data)| (t - start, code.as_str(), data.as_str())),
+        )
+        .map(|(t, code, data)| serde_json::json!([(t * 1000.0).round() / 1000.0, code, data]))
+        .collect();
+
+    let mut header = cast.header.clone();
+    if let Some(timestamp) = header.get("timestamp").and_then(Value::as_u64) {
+        header["timestamp"] = (timestamp + start as u64).into();
+    }
+    if let Some(last) = events.last().and_then(|e| e[0].as_f64()) {
+        header["duration"] = last.into();
+    }
+    std::iter::once(header)
+        .chain(events)
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_characters_wait_for_their_remaining_bytes() {
+        let mut bytes = "ab\u{e9}".as_bytes()[..3].to_vec();
+        assert_eq!(take_utf8(&mut bytes), "ab");
+        assert_eq!(bytes, vec![0xc3]);
+        bytes.extend_from_slice(&[0xa9, 0xff, b'c']);
+        assert_eq!(take_utf8(&mut bytes), "\u{e9}\u{fffd}c");
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn slices_replay_earlier_output_first() {
+        let content = "{\"version\":2,\"width\":80,\"height\":24,\"timestamp\":1000}\n\
+                       [0.5,\"o\",\"$ \"]\n\
+                       [1.5,\"o\",\"ls\"]\n\
+                       [3.0,\"o\",\"\\r\\n\"]\n\
+                       [9.0,\"o\",\"done\"]\n\
+                       [9.5,\"o\",\"cut";
+        let cast = parse_cast(content).unwrap();
+        assert_eq!(cast.events.len(), 4);
+
+        let sliced = parse_cast(&slice(&cast, 1.0, Some(3.0))).unwrap();
+        assert_eq!(sliced.header["timestamp"], 1001);
+        assert_eq!(sliced.header["duration"], 2.0);
+        let events: Vec<(f64, &str)> = sliced
+            .events
+            .iter()
+            .map(|(t, _, data)| (*t, data.as_str()))
+            .collect();
+        assert_eq!(events, vec![(0.0, "$ "), (0.5, "ls"), (2.0, "\r\n")]);
+    }
+}
@@ -0,0 +1,431 @@
+use super::config::{directiv_home, read_app_config};
+use super::logs::shell_quote;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{Emitter, Manager};
+use tauri_plugin_shell::ShellExt;
+
+/// Claude Code hook events reported to directiv. Tool events use the `*` matcher.
+const HOOK_EVENTS: &[(&str, bool)] = &[
+    ("SessionStart", false),
+    ("UserPromptSubmit", false),
+    ("PreToolUse", true),
+    ("PostToolUse", true),
+    ("Notification", false),
+    ("Stop", false),
+];
+
+const SETTINGS_FILE: &str = ".claude/settings.local.json";
+
+/// `agentHooks` in `directiv.config.json`.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AgentHooksConfig {
+    /// Install the hooks into every worktree `worktree_create` returns. Opt-in,
+    /// since it writes into the worktree's Claude Code settings
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum HookState {
+    Working,
+    WaitingPermission,
+    /// Started, or asked for input after sitting idle
+    Idle,
+    /// The agent finished its turn
+    Finished,
+}
+
+/// The state of the agent in one worktree, as reported by its hooks.
+/// Emitted as `agent://state` whenever the state or its detail changes.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskAgentState {
+    pub worktree: String,
+    /// Claude Code's session id
+    pub session_id: Option<String>,
+    pub state: HookState,
+    /// Hook event that set the state
+    pub event: String,
+    /// Tool name or notification message
+    pub detail: Option<String>,
+    /// Unix millis the state was entered
+    pub since: u64,
+    /// Unix millis of the last hook event
+    pub updated: u64,
+}
+
+/// Authoritative agent states, by worktree path.
+#[derive(Default)]
+pub struct AgentHooks(Mutex<HashMap<String, TaskAgentState>>);
+
+impl AgentHooks {
+    pub(crate) fn states(&self) -> Vec<TaskAgentState> {
+        self.0.lock().unwrap().values().cloned().collect()
+    }
+}
+
+/// A line written to the socket by `directiv --agent-hook`.
+#[derive(Debug, Deserialize)]
+struct HookMessage {
+    worktree: String,
+    /// The JSON Claude Code passes the hook on stdin
+    payload: Value,
+}
+
+/// `~/.directiv/agent.sock`
+pub(crate) fn agent_socket_path() -> Result<PathBuf, String> {
+    Ok(directiv_home()?.join("agent.sock"))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// The state a hook event leads to, with its detail. `None` for events that don't
+/// change the state.
+fn transition(payload: &Value) -> Option<(HookState, Option<String>)> {
+    let text = |key: &str| payload.get(key).and_then(Value::as_str).map(str::to_string);
+    match payload.get("hook_event_name")?.as_str()? {
+        "SessionStart" => Some((HookState::Idle, text("source"))),
+        "UserPromptSubmit" => Some((HookState::Working, None)),
+        "PreToolUse" | "PostToolUse" => Some((HookState::Working, text("tool_name"))),
+        "Notification" => {
+            let message = text("message");
+            // Permission requests and the idle reminder both arrive as notifications
+            let permission = message
+                .as_deref()
+                .is_some_and(|m| m.to_lowercase().contains("permission"));
+            let state = if permission {
+                HookState::WaitingPermission
+            } else {
+                HookState::Idle
+            };
+            Some((state, message))
+        }
+        "Stop" => Some((HookState::Finished, None)),
+        _ => None,
+    }
+}
+
+fn apply(app: &tauri::AppHandle, message: HookMessage) {
+    let Some((state, detail)) = transition(&message.payload) else {
+        return;
+    };
+    let event = message.payload["hook_event_name"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let session_id = message
+        .payload
+        .get("session_id")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let now = now_millis();
+
+    let hooks = app.state::<AgentHooks>();
+    let mut states = hooks.0.lock().unwrap();
+    let previous = states.get(&message.worktree);
+    let changed = !matches!(previous, Some(p) if p.state == state && p.detail == detail);
+    let since = match previous {
+        Some(p) if p.state == state => p.since,
+        _ => now,
+    };
+    let current = TaskAgentState {
+        worktree: message.worktree.clone(),
+        session_id,
+        state,
+        event,
+        detail,
+        since,
+        updated: now,
+    };
+    states.insert(message.worktree, current.clone());
+    drop(states);
+
+    if changed {
+        let _ = app.emit("agent://state", current);
+    }
+}
+
+/// Make the directory holding the socket at `path` private to the user.
+#[cfg(unix)]
+fn restrict_permissions(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = path.parent().ok_or("The agent socket has no directory")?;
+    fs::set_permissions(dir, fs::Permissions::from_mode(0o700))
+        .map_err(|e| format!("Failed to restrict {}: {e}", dir.display()))
+}
+
+/// Listen on [`agent_socket_path`] for hook reports, one JSON object per line.
+pub fn spawn_agent_listener(app: tauri::AppHandle) {
+    #[cfg(unix)]
+    tauri::async_runtime::spawn(async move {
+        use tokio::io::{AsyncBufReadExt, BufReader};
+        use tokio::net::UnixListener;
+
+        let path = match agent_socket_path() {
+            Ok(path) => path,
+            Err(e) => {
+                log::warn!("Agent hooks disabled: {e}");
+                return;
+            }
+        };
+        // Only the user may connect: the directory before the socket exists, then
+        // the socket itself
+        if let Err(e) = restrict_permissions(&path) {
+            log::warn!("Agent hooks disabled: {e}");
+            return;
+        }
+        // A socket left behind by a previous run would make bind fail
+        let _ = fs::remove_file(&path);
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::warn!("Failed to listen on {}: {e}", path.display());
+                return;
+            }
+        };
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if let Err(e) = fs::set_permissions(&path, fs::Permissions::from_mode(0o600)) {
+                log::warn!(
+                    "Agent hooks disabled: failed to restrict {}: {e}",
+                    path.display()
+                );
+                return;
+            }
+        }
+
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    log::warn!("Agent socket accept failed: {e}");
+                    continue;
+                }
+            };
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let mut lines = BufReader::new(stream).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    match serde_json::from_str::<HookMessage>(&line) {
+                        Ok(message) => apply(&app, message),
+                        Err(e) => log::warn!("Ignoring agent hook message: {e}"),
+                    }
+                }
+            });
+        }
+    });
+}
+
+/// Hide the settings file from `git status` through the repository's
+/// `info/exclude`, so it never makes a worktree dirty.
+async fn exclude_settings(app: &tauri::AppHandle, worktree_path: &str) -> Result<(), String> {
+    let output = app
+        .shell()
+        .command("git")
+        .args(["-C", worktree_path, "rev-parse", "--git-common-dir"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git rev-parse failed: {stderr}"));
+    }
+    let common_dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let exclude = Path::new(worktree_path)
+        .join(common_dir)
+        .join("info")
+        .join("exclude");
+
+    let pattern = format!("/{SETTINGS_FILE}");
+    let existing = fs::read_to_string(&exclude).unwrap_or_default();
+    if existing.lines().any(|l| l.trim() == pattern) {
+        return Ok(());
+    }
+    if let Some(parent) = exclude.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    let separator = if existing.is_empty() || existing.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    fs::write(&exclude, format!("{existing}{separator}{pattern}\n"))
+        .map_err(|e| format!("Failed to write {}: {e}", exclude.display()))
+}
+
+/// Merge directiv's hooks into `settings`, replacing the ones of a previous install
+/// and keeping everything else.
+fn merge_hooks(settings: &mut Value, command: &str) -> Result<(), String> {
+    let root = settings
+        .as_object_mut()
+        .ok_or("settings must be a JSON object")?;
+    let hooks = root
+        .entry("hooks")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .ok_or("settings: hooks must be an object")?;
+
+    let ours = |group: &Value| {
+        group["hooks"].as_array().is_some_and(|hooks| {
+            hooks.iter().any(|h| {
+                h["command"]
+                    .as_str()
+                    .is_some_and(|c| c.contains("--agent-hook"))
+            })
+        })
+    };
+    for (event, tool_event) in HOOK_EVENTS {
+        let groups = hooks
+            .entry(*event)
+            .or_insert_with(|| json!([]))
+            .as_array_mut()
+            .ok_or_else(|| format!("settings: hooks.{event} must be an array"))?;
+        groups.retain(|group| !ours(group));
+        let mut group = json!({ "hooks": [{ "type": "command", "command": command }] });
+        if *tool_event {
+            group["matcher"] = json!("*");
+        }
+        groups.push(group);
+    }
+    Ok(())
+}
+
+/// Whether the repository tracks the settings file, making it shared rather
+/// than local.
+async fn settings_tracked(app: &tauri::AppHandle, worktree_path: &str) -> Result<bool, String> {
+    let output = app
+        .shell()
+        .command("git")
+        .args(["-C", worktree_path, "ls-files", "--", SETTINGS_FILE])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git ls-files failed: {stderr}"));
+    }
+    Ok(!output.stdout.is_empty())
+}
+
+/// Write the hooks reporting to directiv into the worktree's
+/// `.claude/settings.local.json`, unless the repository tracks that file.
+pub(crate) async fn install_agent_hooks(
+    app: &tauri::AppHandle,
+    worktree_path: &str,
+) -> Result<(), String> {
+    if settings_tracked(app, worktree_path).await? {
+        return Err(format!(
+            "{SETTINGS_FILE} is tracked by git; not writing the hooks into it"
+        ));
+    }
+    let exe = std::env::current_exe().map_err(|e| format!("Cannot locate directiv: {e}"))?;
+    let command = format!(
+        "{} --agent-hook {}",
+        shell_quote(&exe.to_string_lossy()),
+        shell_quote(worktree_path),
+    );
+
+    let path = Path::new(worktree_path).join(SETTINGS_FILE);
+    let mut settings: Value = match fs::read_to_string(&path) {
+        Ok(raw) => serde_json::from_str(&raw)
+            .map_err(|e| format!("Failed to parse {}: {e}", path.display()))?,
+        Err(_) => json!({}),
+    };
+    merge_hooks(&mut settings, &command)?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    let raw = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {e}"))?;
+    fs::write(&path, raw + "\n").map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+
+    exclude_settings(app, worktree_path).await
+}
+
+/// Install the hooks when `agentHooks.enabled`; failures are logged, not returned.
+pub(crate) async fn install_configured_agent_hooks(app: &tauri::AppHandle, worktree_path: &str) {
+    if !read_app_config().agent_hooks.enabled {
+        return;
+    }
+    if let Err(e) = install_agent_hooks(app, worktree_path).await {
+        log::warn!("Failed to install agent hooks in {worktree_path}: {e}");
+    }
+}
+
+#[tauri::command]
+pub async fn agent_hooks_install(
+    app: tauri::AppHandle,
+    worktree_path: String,
+) -> Result<(), String> {
+    install_agent_hooks(&app, &worktree_path).await
+}
+
+#[tauri::command]
+pub async fn agent_states(app: tauri::AppHandle) -> Result<Vec<TaskAgentState>, String> {
+    Ok(app.state::<AgentHooks>().states())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_replaces_previous_install_and_keeps_user_hooks() {
+        let mut settings = json!({
+            "permissions": { "allow": ["Bash(ls)"] },
+            "hooks": {
+                "Stop": [
+                    { "hooks": [{ "type": "command", "command": "say done" }] },
+                    { "hooks": [{ "type": "command", "command": "'/old/directiv' --agent-hook '/wt'" }] }
+                ]
+            }
+        });
+        merge_hooks(&mut settings, "'/new/directiv' --agent-hook '/wt'").unwrap();
+
+        let stop = settings["hooks"]["Stop"].as_array().unwrap();
+        assert_eq!(stop.len(), 2);
+        assert_eq!(stop[0]["hooks"][0]["command"], "say done");
+        assert!(stop[1]["hooks"][0]["command"]
+            .as_str()
+            .unwrap()
+            .starts_with("'/new/"));
+        assert_eq!(settings["hooks"]["PreToolUse"][0]["matcher"], "*");
+        assert!(settings["hooks"]["Notification"][0]
+            .get("matcher")
+            .is_none());
+        assert_eq!(settings["permissions"]["allow"][0], "Bash(ls)");
+    }
+
+    #[test]
+    fn notifications_distinguish_permission_from_idle() {
+        let permission = json!({
+            "hook_event_name": "Notification",
+            "message": "Claude needs your permission to use Bash"
+        });
+        let idle = json!({
+            "hook_event_name": "Notification",
+            "message": "Claude is waiting for your input"
+        });
+        assert_eq!(
+            transition(&permission).unwrap().0,
+            HookState::WaitingPermission
+        );
+        assert_eq!(transition(&idle).unwrap().0, HookState::Idle);
+        assert!(transition(&json!({ "hook_event_name": "SubagentStop" })).is_none());
+    }
+}
@@ -0,0 +1,355 @@
+use super::agent_hooks::{AgentHooks, HookState};
+use super::layout::tmux_arg;
+use super::tmux::{
+    decode_option, directiv_sessions, encode_option, tmux_agent_states, tmux_command, tmux_paste,
+    tmux_send_keys, TmuxSession,
+};
+use crate::agent_state::AgentState;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::Manager;
+
+/// Session user options read by broadcasts.
+const OPT_LABELS: &str = "@directiv-labels";
+const OPT_BROADCAST: &str = "@directiv-broadcast";
+
+/// Whether a session takes broadcasts, set per session with
+/// `tmux_set_broadcast_rule`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum BroadcastRule {
+    #[default]
+    Allow,
+    /// Only when the session is listed in [`BroadcastOptions::confirmed`]
+    Confirm,
+    Deny,
+}
+
+impl BroadcastRule {
+    fn parse(value: &str) -> Self {
+        match value {
+            "confirm" => Self::Confirm,
+            "deny" => Self::Deny,
+            _ => Self::Allow,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Confirm => "confirm",
+            Self::Deny => "deny",
+        }
+    }
+}
+
+/// Which sessions a broadcast goes to. Every field narrows the selection; an
+/// empty one matches everything.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BroadcastFilter {
+    pub sessions: Vec<String>,
+    /// Repo paths
+    pub repos: Vec<String>,
+    pub states: Vec<AgentState>,
+    /// Sessions carrying any of these labels
+    pub labels: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BroadcastOptions {
+    /// Send as a bracketed paste (multi-line text) rather than typed keys
+    pub paste: bool,
+    /// Leave the text in the input without pressing Enter
+    pub no_submit: bool,
+    /// Only report which sessions would receive it
+    pub dry_run: bool,
+    /// Sessions with the `confirm` rule that the user agreed to send to
+    pub confirmed: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DeliveryStatus {
+    Delivered,
+    /// Would be delivered; dry runs only
+    Matched,
+    NeedsConfirmation,
+    /// The session's rule is `deny`
+    Refused,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcastResult {
+    pub session: String,
+    pub issue: Option<String>,
+    pub status: DeliveryStatus,
+    pub error: Option<String>,
+}
+
+/// Labels and broadcast rule of each session.
+async fn session_options(
+    app: &tauri::AppHandle,
+) -> Result<HashMap<String, (Vec<String>, BroadcastRule)>, String> {
+    let output = tmux_command(app)
+        .args([
+            "list-sessions",
+            "-F",
+            &format!("#{{session_name}}|#{{{OPT_BROADCAST}}}|#{{{OPT_LABELS}}}"),
+        ])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run tmux: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("tmux list-sessions failed: {stderr}"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '|');
+            let name = parts.next()?.to_string();
+            let rule = BroadcastRule::parse(parts.next().unwrap_or_default());
+            let labels = parts
+                .next()
+                .and_then(decode_option)
+                .map(|l| l.split(',').map(str::to_string).collect())
+                .unwrap_or_default();
+            Some((name, (labels, rule)))
+        })
+        .collect())
+}
+
+/// Agent state by session name, preferring what the agent's hooks reported.
+async fn agent_states(
+    app: &tauri::AppHandle,
+    sessions: &[TmuxSession],
+) -> Result<HashMap<String, AgentState>, String> {
+    let names = sessions.iter().map(|s| s.name.clone()).collect();
+    let mut states: HashMap<String, AgentState> = tmux_agent_states(app.clone(), Some(names))
+        .await?
+        .into_iter()
+        .map(|s| (s.session, s.detection.state))
+        .collect();
+    let hooks: HashMap<String, HookState> = app
+        .state::<AgentHooks>()
+        .states()
+        .into_iter()
+        .map(|s| (s.worktree, s.state))
+        .collect();
+    for session in sessions {
+        let Some(state) = session.worktree.as_ref().and_then(|w| hooks.get(w)) else {
+            continue;
+        };
+        let state = match state {
+            HookState::Working => AgentState::Working,
+            HookState::WaitingPermission => AgentState::Permission,
+            HookState::Idle | HookState::Finished => AgentState::Idle,
+        };
+        states.insert(session.name.clone(), state);
+    }
+    Ok(states)
+}
+
+fn matches(
+    filter: &BroadcastFilter,
+    session: &TmuxSession,
+    labels: &[String],
+    state: Option<AgentState>,
+) -> bool {
+    let any = |wanted: &[String], value: Option<&String>| {
+        wanted.is_empty() || value.is_some_and(|v| wanted.contains(v))
+    };
+    any(&filter.sessions, Some(&session.name))
+        && any(&filter.repos, session.repo.as_ref())
+        && (filter.states.is_empty() || state.is_some_and(|s| filter.states.contains(&s)))
+        && (filter.labels.is_empty() || labels.iter().any(|l| filter.labels.contains(l)))
+}
+
+/// Send `text` to the agent pane of every directiv session matching `filter`,
+/// honoring each session's broadcast rule. Returns one result per matching
+/// session, in session order.
+#[tauri::command]
+pub async fn tmux_broadcast(
+    app: tauri::AppHandle,
+    text: String,
+    filter: Option<BroadcastFilter>,
+    options: Option<BroadcastOptions>,
+) -> Result<Vec<BroadcastResult>, String> {
+    let filter = filter.unwrap_or_default();
+    let options = options.unwrap_or_default();
+    if text.trim().is_empty() {
+        return Err("Nothing to broadcast".to_string());
+    }
+
+    let sessions = directiv_sessions(&app).await?;
+    let session_options = session_options(&app).await?;
+    let states = if filter.states.is_empty() {
+        HashMap::new()
+    } else {
+        agent_states(&app, &sessions).await?
+    };
+
+    let mut results = Vec::new();
+    for session in sessions {
+        let (labels, rule) = session_options
+            .get(&session.name)
+            .cloned()
+            .unwrap_or_default();
+        let state = states.get(&session.name).copied();
+        if !matches(&filter, &session, &labels, state) {
+            continue;
+        }
+
+        let mut result = BroadcastResult {
+            session: session.name.clone(),
+            issue: session.issue.clone(),
+            status: DeliveryStatus::Matched,
+            error: None,
+        };
+        let confirmed = options.confirmed.contains(&session.name);
+        if rule == BroadcastRule::Deny {
+            result.status = DeliveryStatus::Refused;
+        } else if rule == BroadcastRule::Confirm && !confirmed {
+            result.status = DeliveryStatus::NeedsConfirmation;
+        } else if !options.dry_run {
+            let delivery = if options.paste {
+                let submit = Some(!options.no_submit);
+                tmux_paste(
+                    app.clone(),
+                    session.name,
+                    text.clone(),
+                    session.agent_pane,
+                    submit,
+                )
+                .await
+            } else if options.no_submit {
+                let target = session
+                    .agent_pane
+                    .unwrap_or_else(|| format!("={}:", session.name));
+                send_text(&app, &target, &text).await
+            } else {
+                tmux_send_keys(app.clone(), session.name, text.clone(), session.agent_pane).await
+            };
+            match delivery {
+                Ok(()) => result.status = DeliveryStatus::Delivered,
+                Err(e) => {
+                    result.status = DeliveryStatus::Failed;
+                    result.error = Some(e);
+                }
+            }
+        }
+        results.push(result);
+    }
+    Ok(results)
+}
+
+/// Type `text` without pressing Enter.
+async fn send_text(app: &tauri::AppHandle, target: &str, text: &str) -> Result<(), String> {
+    let output = tmux_command(app)
+        .args(["send-keys", "-t", target, "-l", text])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run tmux: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("tmux send-keys failed: {stderr}"));
+    }
+    Ok(())
+}
+
+async fn set_session_option(
+    app: &tauri::AppHandle,
+    session: &str,
+    option: &str,
+    value: &str,
+) -> Result<(), String> {
+    let target = format!("={session}");
+    let mut args = vec!["set-option", "-t", &target];
+    let value = tmux_arg(&encode_option(value));
+    if value.is_empty() {
+        args.extend(["-u", option]);
+    } else {
+        args.extend([option, &value]);
+    }
+    let output = tmux_command(app)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run tmux: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("tmux set-option failed: {stderr}"));
+    }
+    Ok(())
+}
+
+/// Replace the labels broadcasts can select `session` by.
+#[tauri::command]
+pub async fn tmux_set_labels(
+    app: tauri::AppHandle,
+    session: String,
+    labels: Vec<String>,
+) -> Result<(), String> {
+    let labels: Vec<&str> = labels
+        .iter()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .collect();
+    if labels.iter().any(|l| l.contains(',')) {
+        return Err("Labels cannot contain commas".to_string());
+    }
+    set_session_option(&app, &session, OPT_LABELS, &labels.join(",")).await
+}
+
+#[tauri::command]
+pub async fn tmux_set_broadcast_rule(
+    app: tauri::AppHandle,
+    session: String,
+    rule: BroadcastRule,
+) -> Result<(), String> {
+    set_session_option(&app, &session, OPT_BROADCAST, rule.as_str()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(name: &str, repo: Option<&str>) -> TmuxSession {
+        TmuxSession {
+            name: name.to_string(),
+            attached: false,
+            windows: 1,
+            created: String::new(),
+            issue: None,
+            repo: repo.map(str::to_string),
+            worktree: None,
+            agent: None,
+            agent_pane: None,
+        }
+    }
+
+    #[test]
+    fn every_filter_field_narrows_the_selection() {
+        let api = session("eng-1", Some("/src/api"));
+        let labels = vec!["backend".to_string()];
+        let idle = Some(AgentState::Idle);
+
+        assert!(matches(&BroadcastFilter::default(), &api, &[], None));
+
+        let filter = BroadcastFilter {
+            repos: vec!["/src/api".to_string()],
+            states: vec![AgentState::Idle, AgentState::Permission],
+            labels: vec!["backend".to_string(), "urgent".to_string()],
+            ..Default::default()
+        };
+        assert!(matches(&filter, &api, &labels, idle));
+        assert!(!matches(&filter, &api, &labels, Some(AgentState::Working)));
+        assert!(!matches(&filter, &api, &labels, None));
+        assert!(!matches(&filter, &api, &[], idle));
+        assert!(!matches(&filter, &session("eng-2", None), &labels, idle));
+    }
+}
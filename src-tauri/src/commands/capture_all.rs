@@ -0,0 +1,194 @@
+use super::gc::now_secs;
+use super::tmux::{
+    capture_args, directiv_sessions, tmux_command, tmux_list_sessions, CaptureOptions, TmuxSession,
+};
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+
+#[derive(Default)]
+struct CaptureState {
+    /// Cursor of the latest batch
+    cursor: u64,
+    /// Hash of each session's pane and the cursor of the batch it last changed in
+    panes: HashMap<String, (String, u64)>,
+}
+
+/// What previous batches captured, to tell which panes changed since a cursor.
+#[derive(Default)]
+pub struct CaptureCursor(Mutex<CaptureState>);
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PaneCapture {
+    pub session: String,
+    /// The agent pane's id, or `None` for the session's active pane
+    pub pane: Option<String>,
+    pub hash: String,
+    /// Cursor of the batch the content last changed in
+    pub changed: u64,
+    /// Only when the content changed since the requested cursor
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureBatch {
+    /// Pass back as `since` to receive only what changes next
+    pub cursor: u64,
+    /// Every directiv session, whether its pane changed or not
+    pub panes: Vec<PaneCapture>,
+}
+
+fn content_hash(content: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Split the output of a capture sequence on the `marker` line printed before
+/// each capture; `None` when the count doesn't match `expected`.
+fn split_captures(stdout: &str, marker: &str, expected: usize) -> Option<Vec<String>> {
+    let separator = format!("{marker}\n");
+    let mut parts = stdout.split(separator.as_str());
+    if !parts.next()?.is_empty() {
+        return None;
+    }
+    let captures: Vec<String> = parts.map(str::to_string).collect();
+    (captures.len() == expected).then_some(captures)
+}
+
+/// Capture the pane of each session in a single tmux invocation, which fails as
+/// a whole when any of the sessions is gone.
+async fn capture_sessions(
+    app: &tauri::AppHandle,
+    sessions: &[TmuxSession],
+    options: &CaptureOptions,
+) -> Result<Vec<String>, String> {
+    if sessions.is_empty() {
+        return Ok(Vec::new());
+    }
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let marker = format!("directiv-capture-{nanos}");
+    let mut args = Vec::new();
+    for session in sessions {
+        let target = session
+            .agent_pane
+            .clone()
+            .unwrap_or_else(|| format!("={}:", session.name));
+        if !args.is_empty() {
+            args.push(";".to_string());
+        }
+        args.extend(["display-message".to_string(), "-p".to_string()]);
+        args.push(marker.clone());
+        args.push(";".to_string());
+        args.extend(capture_args(&target, options)?);
+    }
+
+    let output = tmux_command(app)
+        .args(&args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run tmux: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("tmux capture-pane failed: {stderr}"));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    split_captures(&stdout, &marker, sessions.len())
+        .ok_or_else(|| "tmux capture-pane returned an unexpected number of panes".to_string())
+}
+
+/// The agent pane (else the active pane) of each of `sessions`, captured in one
+/// tmux invocation. When a session ended between listing and capturing, the ones
+/// left are captured again; `options.window` is ignored.
+pub(crate) async fn capture_each(
+    app: &tauri::AppHandle,
+    sessions: Vec<TmuxSession>,
+    options: &CaptureOptions,
+) -> Result<Vec<(TmuxSession, String)>, String> {
+    if let Ok(captures) = capture_sessions(app, &sessions, options).await {
+        return Ok(sessions.into_iter().zip(captures).collect());
+    }
+    let wanted: HashSet<String> = sessions.into_iter().map(|s| s.name).collect();
+    let left: Vec<TmuxSession> = tmux_list_sessions(app.clone())
+        .await?
+        .into_iter()
+        .filter(|s| wanted.contains(&s.name))
+        .collect();
+    let captures = capture_sessions(app, &left, options).await?;
+    Ok(left.into_iter().zip(captures).collect())
+}
+
+/// Capture the agent pane (else the active pane) of every directiv session in a
+/// single tmux invocation. Each pane comes with the hash of its content; the
+/// content itself only when it changed after batch `since`, or always without
+/// `since`. `options.window` is ignored. Cursors keep increasing across restarts
+/// of the app, so a stale one only means everything is sent again.
+#[tauri::command]
+pub async fn tmux_capture_all(
+    app: tauri::AppHandle,
+    since: Option<u64>,
+    options: Option<CaptureOptions>,
+) -> Result<CaptureBatch, String> {
+    let options = options.unwrap_or_default();
+    let captures = capture_each(&app, directiv_sessions(&app).await?, &options).await?;
+
+    let cursors = app.state::<CaptureCursor>();
+    let mut state = cursors.0.lock().unwrap();
+    // Never behind the clock in milliseconds, so cursors keep increasing across restarts
+    state.cursor = (state.cursor + 1).max(now_secs() * 1000);
+    let cursor = state.cursor;
+    let mut panes = Vec::new();
+    let mut seen = HashMap::new();
+    for (session, content) in captures {
+        let hash = content_hash(&content);
+        let changed = match state.panes.get(&session.name) {
+            Some((previous, changed)) if *previous == hash => *changed,
+            _ => cursor,
+        };
+        seen.insert(session.name.clone(), (hash.clone(), changed));
+        panes.push(PaneCapture {
+            session: session.name,
+            pane: session.agent_pane,
+            hash,
+            changed,
+            content: since
+                .map_or(true, |since| changed > since)
+                .then_some(content),
+        });
+    }
+    state.panes = seen;
+    Ok(CaptureBatch { cursor, panes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_split_on_the_marker() {
+        let out =
+            "directiv-capture-1\n$ ls\nsrc\n\ndirectiv-capture-1\ndirectiv-capture-1\n> thinking\n";
+        assert_eq!(
+            split_captures(out, "directiv-capture-1", 3),
+            Some(vec![
+                "$ ls\nsrc\n\n".to_string(),
+                String::new(),
+                "> thinking\n".to_string()
+            ])
+        );
+        assert_eq!(split_captures(out, "directiv-capture-1", 2), None);
+        assert_eq!(
+            split_captures("stray\ndirectiv-capture-1\n", "directiv-capture-1", 1),
+            None
+        );
+    }
+}
@@ -1,6 +1,32 @@
+use super::agent_hooks::AgentHooksConfig;
+use super::gc::WorktreeGcConfig;
+use super::logs::LogsConfig;
+use super::reaper::SessionReaperConfig;
+use super::tmux::TmuxConfig;
+use super::web::WebViewConfig;
+use serde::Deserialize;
 use std::fs;
 use std::path::PathBuf;
 
+/// The parts of `directiv.config.json` the backend acts on by itself.
+/// The frontend owns the rest; unknown keys are ignored here.
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppConfig {
+    pub workspaces: Vec<WorkspaceEntry>,
+    pub worktree_gc: WorktreeGcConfig,
+    pub tmux: TmuxConfig,
+    pub logs: LogsConfig,
+    pub agent_hooks: AgentHooksConfig,
+    pub session_reaper: SessionReaperConfig,
+    pub web_view: WebViewConfig,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct WorkspaceEntry {
+    pub path: String,
+}
+
 #[tauri::command]
 pub async fn load_config() -> Result<String, String> {
     let path = find_config_file()?;
@@ -25,3 +51,30 @@ fn find_config_file() -> Result<PathBuf, String> {
 
     Err("directiv.config.json not found".into())
 }
+
+/// Read the backend config, falling back to defaults when the file is missing or invalid.
+pub(crate) fn read_app_config() -> AppConfig {
+    let Ok(path) = find_config_file() else {
+        return AppConfig::default();
+    };
+    match fs::read_to_string(&path).map(|raw| serde_json::from_str(&raw)) {
+        Ok(Ok(config)) => config,
+        Ok(Err(e)) => {
+            log::warn!("Failed to parse {}: {e}", path.display());
+            AppConfig::default()
+        }
+        Err(e) => {
+            log::warn!("Failed to read {}: {e}", path.display());
+            AppConfig::default()
+        }
+    }
+}
+
+/// `~/.directiv`, where the backend keeps its own state and logs.
+pub(crate) fn directiv_home() -> Result<PathBuf, String> {
+    let dir = dirs::home_dir()
+        .ok_or("Cannot resolve home directory")?
+        .join(".directiv");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    Ok(dir)
+}
@@ -0,0 +1,345 @@
+use super::config::{directiv_home, read_app_config};
+use super::pool::pool_dir;
+use super::tmux::{session_name, tmux_list_sessions, TmuxSession};
+use super::workspace::list_repo_dirs;
+use super::worktree::{
+    branch_landed, detect_default_branch, parse_worktree_porcelain, worktree_remove, worktrees_base,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri_plugin_shell::ShellExt;
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// `worktreeGc` in `directiv.config.json`.
+///
+/// Merged worktrees are removed together with their branch; idle unmerged ones are
+/// archived, i.e. the worktree is removed but the branch is kept so the task can be
+/// picked up again. Dirty worktrees and worktrees with a live tmux session are never
+/// touched. `None` disables a rule.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WorktreeGcConfig {
+    /// Run the policy in the background (previews work regardless)
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    pub remove_merged_after_days: Option<u64>,
+    pub archive_idle_after_days: Option<u64>,
+}
+
+impl Default for WorktreeGcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: 60,
+            remove_merged_after_days: Some(2),
+            archive_idle_after_days: Some(30),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GcAction {
+    Keep,
+    Remove,
+    Archive,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GcDecision {
+    pub repo_path: String,
+    pub branch: String,
+    pub path: String,
+    pub action: GcAction,
+    pub reason: String,
+    pub idle_days: u64,
+    /// False in preview mode, or when the action failed
+    pub applied: bool,
+    pub error: Option<String>,
+    /// Unix seconds
+    pub timestamp: u64,
+}
+
+pub(crate) fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn gc_log_path() -> Result<PathBuf, String> {
+    Ok(directiv_home()?.join("worktree-gc.log"))
+}
+
+fn append_gc_log(decisions: &[GcDecision]) -> Result<(), String> {
+    let path = gc_log_path()?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    for decision in decisions {
+        let line = serde_json::to_string(decision).map_err(|e| e.to_string())?;
+        writeln!(file, "{line}").map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Seconds since anything happened in a worktree: its last commit or its index.
+async fn last_activity(app: &tauri::AppHandle, worktree_path: &str) -> Option<u64> {
+    let mut latest = None;
+
+    if let Ok(out) = app
+        .shell()
+        .command("git")
+        .args(["-C", worktree_path, "log", "-1", "--format=%ct"])
+        .output()
+        .await
+    {
+        if out.status.success() {
+            latest = String::from_utf8_lossy(&out.stdout).trim().parse().ok();
+        }
+    }
+
+    if let Ok(out) = app
+        .shell()
+        .command("git")
+        .args(["-C", worktree_path, "rev-parse", "--git-path", "index"])
+        .output()
+        .await
+    {
+        let index = PathBuf::from(String::from_utf8_lossy(&out.stdout).trim());
+        let index = if index.is_absolute() {
+            index
+        } else {
+            Path::new(worktree_path).join(index)
+        };
+        let modified = fs::metadata(index)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+        latest = latest.max(modified);
+    }
+
+    latest.map(|t| now_secs().saturating_sub(t))
+}
+
+/// Whether a worktree has uncommitted changes. One that cannot be inspected
+/// counts as dirty.
+pub(crate) async fn worktree_dirty(app: &tauri::AppHandle, worktree_path: &str) -> bool {
+    match app
+        .shell()
+        .command("git")
+        .args(["-C", worktree_path, "status", "--porcelain"])
+        .output()
+        .await
+    {
+        Ok(out) if out.status.success() => !String::from_utf8_lossy(&out.stdout).trim().is_empty(),
+        _ => true,
+    }
+}
+
+fn has_tmux_session(sessions: &[TmuxSession], branch: &str, path: &str) -> bool {
+    let name = session_name(branch);
+    sessions.iter().any(|s| match &s.worktree {
+        Some(worktree) => worktree == path,
+        // Sessions without metadata are matched by name only
+        None => s.name == name,
+    })
+}
+
+async fn evaluate_repo(
+    app: &tauri::AppHandle,
+    config: &WorktreeGcConfig,
+    repo_path: &str,
+    preview: bool,
+) -> Result<Vec<GcDecision>, String> {
+    let output = app
+        .shell()
+        .command("git")
+        .args(["-C", repo_path, "worktree", "list", "--porcelain"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git worktree list failed: {stderr}"));
+    }
+
+    let pool = pool_dir(&worktrees_base(Path::new(repo_path))?);
+    let default_branch = detect_default_branch(app, repo_path).await;
+    // A tmux failure must not make sessions look absent
+    let sessions = tmux_list_sessions(app.clone()).await?;
+    let mut decisions = Vec::new();
+
+    // Skip the main worktree (first entry), detached and pooled worktrees
+    for wt in parse_worktree_porcelain(&String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .skip(1)
+        .filter(|wt| !wt.branch.is_empty() && !Path::new(&wt.path).starts_with(&pool))
+    {
+        let idle_secs = last_activity(app, &wt.path).await.unwrap_or(0);
+        let mut decision = GcDecision {
+            repo_path: repo_path.to_string(),
+            branch: wt.branch.clone(),
+            path: wt.path.clone(),
+            action: GcAction::Keep,
+            reason: String::new(),
+            idle_days: idle_secs / SECS_PER_DAY,
+            applied: false,
+            error: None,
+            timestamp: now_secs(),
+        };
+
+        let dirty = worktree_dirty(app, &wt.path).await;
+        let merged = branch_landed(app, repo_path, &wt.branch, &default_branch)
+            .await
+            .unwrap_or(false);
+        let past = |days: Option<u64>| days.is_some_and(|d| idle_secs >= d * SECS_PER_DAY);
+
+        if dirty {
+            decision.reason = "Uncommitted changes".to_string();
+        } else if has_tmux_session(&sessions, &wt.branch, &wt.path) {
+            decision.reason = "tmux session is running".to_string();
+        } else if merged && past(config.remove_merged_after_days) {
+            decision.action = GcAction::Remove;
+            decision.reason = format!("Merged, idle for {} days", decision.idle_days);
+        } else if !merged && past(config.archive_idle_after_days) {
+            decision.action = GcAction::Archive;
+            decision.reason = format!("Unmerged, idle for {} days", decision.idle_days);
+        } else {
+            decision.reason = if merged {
+                "Merged, recently active".to_string()
+            } else {
+                "Unmerged, recently active".to_string()
+            };
+        }
+
+        if !preview && decision.action != GcAction::Keep {
+            let result = worktree_remove(
+                app.clone(),
+                repo_path.to_string(),
+                wt.path.clone(),
+                Some(wt.branch.clone()),
+                Some(decision.action == GcAction::Remove),
+            )
+            .await;
+            match result {
+                Ok(()) => decision.applied = true,
+                Err(e) => decision.error = Some(e),
+            }
+        }
+
+        decisions.push(decision);
+    }
+
+    Ok(decisions)
+}
+
+async fn run_gc(
+    app: &tauri::AppHandle,
+    config: &WorktreeGcConfig,
+    workspaces: &[String],
+    preview: bool,
+) -> Result<Vec<GcDecision>, String> {
+    let mut decisions = Vec::new();
+    for workspace in workspaces {
+        let repos = match list_repo_dirs(Path::new(workspace)) {
+            Ok(repos) => repos,
+            Err(e) => {
+                log::warn!("Worktree GC: skipping workspace {workspace}: {e}");
+                continue;
+            }
+        };
+        for repo in repos {
+            let repo_path = repo.to_string_lossy().to_string();
+            match evaluate_repo(app, config, &repo_path, preview).await {
+                Ok(mut repo_decisions) => decisions.append(&mut repo_decisions),
+                Err(e) => log::warn!("Worktree GC: skipping {repo_path}: {e}"),
+            }
+        }
+    }
+
+    if !preview {
+        let acted: Vec<GcDecision> = decisions
+            .iter()
+            .filter(|d| d.action != GcAction::Keep)
+            .cloned()
+            .collect();
+        if !acted.is_empty() {
+            append_gc_log(&acted)?;
+        }
+    }
+
+    Ok(decisions)
+}
+
+/// Evaluate the GC policy over every workspace repo.
+/// Runs as a preview (nothing removed, nothing logged) unless `preview` is `false`.
+#[tauri::command]
+pub async fn worktree_gc_run(
+    app: tauri::AppHandle,
+    preview: Option<bool>,
+) -> Result<Vec<GcDecision>, String> {
+    let config = read_app_config();
+    let workspaces: Vec<String> = config.workspaces.iter().map(|w| w.path.clone()).collect();
+    run_gc(
+        &app,
+        &config.worktree_gc,
+        &workspaces,
+        preview != Some(false),
+    )
+    .await
+}
+
+/// Most recent GC actions, newest first.
+#[tauri::command]
+pub async fn worktree_gc_log(limit: Option<usize>) -> Result<Vec<GcDecision>, String> {
+    let path = gc_log_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file =
+        fs::File::open(&path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+
+    let mut entries: Vec<GcDecision> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect();
+    entries.reverse();
+    entries.truncate(limit.unwrap_or(100));
+    Ok(entries)
+}
+
+/// Apply the GC policy on its configured interval when `worktreeGc.enabled` is set.
+/// The config is re-read every tick so edits apply without a restart.
+pub fn spawn_gc_scheduler(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = read_app_config();
+            let interval = config.worktree_gc.interval_minutes.max(1);
+            tokio::time::sleep(Duration::from_secs(interval * 60)).await;
+
+            let config = read_app_config();
+            if !config.worktree_gc.enabled {
+                continue;
+            }
+            let workspaces: Vec<String> =
+                config.workspaces.iter().map(|w| w.path.clone()).collect();
+            match run_gc(&app, &config.worktree_gc, &workspaces, false).await {
+                Ok(decisions) => {
+                    let acted = decisions.iter().filter(|d| d.applied).count();
+                    log::info!("Worktree GC: {acted} worktree(s) removed or archived");
+                }
+                Err(e) => log::warn!("Worktree GC failed: {e}"),
+            }
+        }
+    });
+}
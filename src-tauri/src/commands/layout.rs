@@ -0,0 +1,182 @@
+use serde::Deserialize;
+use std::path::{Component, Path};
+
+/// Pane user option naming a layout pane, so it can be targeted by name.
+pub(crate) const OPT_PANE: &str = "@directiv-pane";
+/// Session user option holding the id (`%N`) of the pane the agent runs in.
+pub(crate) const OPT_AGENT_PANE: &str = "@directiv-agent-pane";
+
+/// `layout` in `.directiv.json`: the windows of every task session.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionLayout {
+    #[serde(default)]
+    pub windows: Vec<LayoutWindow>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutWindow {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub panes: Vec<LayoutPane>,
+    /// tmux layout applied once the panes exist (default `tiled`)
+    pub layout: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutPane {
+    pub name: Option<String>,
+    /// Run in the pane's shell; the pane stays open when it exits
+    pub command: Option<String>,
+    /// Working directory relative to the worktree
+    pub cwd: Option<String>,
+    /// The pane directiv launches the agent in. Defaults to the first pane.
+    #[serde(default)]
+    pub agent: bool,
+}
+
+/// tmux splits command sequences on arguments ending in `;` — escape a trailing one.
+pub(crate) fn tmux_arg(value: &str) -> String {
+    match value.strip_suffix(';') {
+        Some(rest) => format!("{rest}\\;"),
+        None => value.to_string(),
+    }
+}
+
+fn pane_dir(working_dir: Option<&str>, cwd: Option<&str>) -> Result<Option<String>, String> {
+    let Some(cwd) = cwd.filter(|c| !c.is_empty() && *c != ".") else {
+        return Ok(working_dir.map(str::to_string));
+    };
+    let rel = Path::new(cwd);
+    if rel.is_absolute() || rel.components().any(|c| matches!(c, Component::ParentDir)) {
+        return Err(format!("layout: cwd must stay inside the worktree: {cwd}"));
+    }
+    Ok(Some(match working_dir {
+        Some(dir) => Path::new(dir).join(rel).to_string_lossy().to_string(),
+        None => cwd.to_string(),
+    }))
+}
+
+/// Arguments of the single tmux invocation creating session `name` with `layout`.
+///
+/// `env` goes into the session environment, which every pane inherits. Every pane
+/// runs an interactive shell. The agent pane runs `agent_shell` (which
+/// signals readiness) and records its id in [`OPT_AGENT_PANE`]; other panes run
+/// their command inside a shell that stays open afterwards. Each step targets
+/// `=name:`, i.e. the window or pane created by the previous one.
+pub(crate) fn layout_args(
+    name: &str,
+    working_dir: Option<&str>,
+    layout: Option<&SessionLayout>,
+    agent_shell: &str,
+    env: &[(String, String)],
+) -> Result<Vec<String>, String> {
+    let mut windows = layout.map(|l| l.windows.clone()).unwrap_or_default();
+    if windows.is_empty() {
+        windows.push(LayoutWindow::default());
+    }
+    for window in &mut windows {
+        if window.panes.is_empty() {
+            window.panes.push(LayoutPane::default());
+        }
+    }
+
+    let agent_at = windows
+        .iter()
+        .enumerate()
+        .find_map(|(w, window)| window.panes.iter().position(|p| p.agent).map(|p| (w, p)))
+        .unwrap_or((0, 0));
+
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
+    let session = format!("={name}");
+    let current = format!("={name}:");
+    let mut args: Vec<String> = Vec::new();
+    let mut step = |parts: Vec<String>| {
+        if !args.is_empty() {
+            args.push(";".to_string());
+        }
+        args.extend(parts);
+    };
+
+    for (w, window) in windows.iter().enumerate() {
+        for (p, pane) in window.panes.iter().enumerate() {
+            let mut parts: Vec<String> = match (w, p) {
+                (0, 0) => {
+                    let mut parts: Vec<String> =
+                        vec!["new-session".into(), "-d".into(), "-s".into(), name.into()];
+                    for (key, value) in env {
+                        parts.extend(["-e".to_string(), tmux_arg(&format!("{key}={value}"))]);
+                    }
+                    parts
+                }
+                (_, 0) => vec![
+                    "new-window".into(),
+                    "-a".into(),
+                    "-t".into(),
+                    current.clone(),
+                ],
+                _ => vec!["split-window".into(), "-t".into(), current.clone()],
+            };
+            if p == 0 {
+                if let Some(window_name) = &window.name {
+                    parts.extend(["-n".to_string(), tmux_arg(window_name)]);
+                }
+            }
+            if let Some(dir) = pane_dir(working_dir, pane.cwd.as_deref())? {
+                parts.extend(["-c".to_string(), tmux_arg(&dir)]);
+            }
+
+            let is_agent = (w, p) == agent_at;
+            if is_agent {
+                parts.push(tmux_arg(agent_shell));
+            } else if let Some(command) = &pane.command {
+                let command = command.replace('\'', r"'\''");
+                parts.push(tmux_arg(&format!("{shell} -ic '{command}; exec {shell}'")));
+            }
+            step(parts);
+
+            if let Some(pane_name) = &pane.name {
+                step(vec![
+                    "set-option".into(),
+                    "-p".into(),
+                    "-t".into(),
+                    current.clone(),
+                    OPT_PANE.into(),
+                    tmux_arg(pane_name),
+                ]);
+            }
+            if is_agent {
+                step(vec![
+                    "set-option".into(),
+                    "-F".into(),
+                    "-t".into(),
+                    session.clone(),
+                    OPT_AGENT_PANE.into(),
+                    "#{pane_id}".into(),
+                ]);
+            }
+        }
+
+        if window.panes.len() > 1 {
+            let arrangement = window.layout.as_deref().unwrap_or("tiled");
+            step(vec![
+                "select-layout".into(),
+                "-t".into(),
+                current.clone(),
+                tmux_arg(arrangement),
+            ]);
+        }
+    }
+
+    if windows.len() > 1 {
+        step(vec![
+            "select-window".into(),
+            "-t".into(),
+            format!("={name}:^"),
+        ]);
+    }
+
+    Ok(args)
+}
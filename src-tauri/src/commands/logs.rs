@@ -0,0 +1,333 @@
+use super::config::{directiv_home, read_app_config};
+use super::tmux::{directiv_sessions, tmux_command};
+use crate::cast::{parse_cast, slice};
+use crate::refname::worktree_dir_name;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// `logs` in `directiv.config.json`: recording task sessions through `pipe-pane`.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogsConfig {
+    /// Log new sessions unless `tmux_create_session` says otherwise
+    pub enabled: bool,
+    /// Size at which a log is rotated to a new file
+    pub max_file_mb: u64,
+    /// Logs kept per task, oldest deleted first
+    pub max_files: usize,
+    /// Also record logged sessions as asciicast files
+    pub record: bool,
+}
+
+impl Default for LogsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_file_mb: 10,
+            max_files: 20,
+            record: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFile {
+    pub name: String,
+    pub size: u64,
+    /// Unix seconds
+    pub modified: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LogPage {
+    pub lines: Vec<String>,
+    pub offset: usize,
+    pub total_lines: usize,
+    /// Offset of the following page, `None` at the end of the log
+    pub next_offset: Option<usize>,
+}
+
+/// An asciicast v2 file of a task.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Recording {
+    #[serde(flatten)]
+    pub file: LogFile,
+    /// Seconds up to the last recorded output
+    pub duration: Option<f64>,
+}
+
+/// `~/.directiv/logs/<repo>/<task>`. Both parts are encoded like worktree
+/// directories, so they are always a single path component and distinct names
+/// never share a directory.
+pub(crate) fn task_log_dir(repo_path: &str, task: &str) -> Result<PathBuf, String> {
+    let repo = Path::new(repo_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let task = worktree_dir_name(task);
+    if matches!(task.as_str(), "" | "." | "..") {
+        return Err(format!("Invalid task name: {task:?}"));
+    }
+    Ok(directiv_home()?
+        .join("logs")
+        .join(worktree_dir_name(&repo))
+        .join(task))
+}
+
+/// A `.log` or `.cast` file of the task.
+fn log_path(repo_path: &str, task: &str, name: &str, extension: &str) -> Result<PathBuf, String> {
+    let valid = name.ends_with(&format!(".{extension}"))
+        && !name.starts_with('.')
+        && !name.contains(['/', '\\']);
+    if !valid {
+        return Err(format!("Invalid log name: {name}"));
+    }
+    Ok(task_log_dir(repo_path, task)?.join(name))
+}
+
+pub(crate) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// `WxH` of a pane, for the header of its recording.
+async fn pane_size(app: &tauri::AppHandle, pane: &str) -> Result<String, String> {
+    let output = tmux_command(app)
+        .args([
+            "display-message",
+            "-p",
+            "-t",
+            pane,
+            "#{pane_width}x#{pane_height}",
+        ])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run tmux: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("tmux display-message failed: {stderr}"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Start piping `pane`'s output into a new log of the task, and into a new
+/// recording with `record`. Replaces the pane's current sink, if any.
+pub(crate) async fn start_pane_log(
+    app: &tauri::AppHandle,
+    pane: &str,
+    repo_path: &str,
+    task: &str,
+    config: &LogsConfig,
+    record: bool,
+) -> Result<(), String> {
+    let dir = task_log_dir(repo_path, task)?;
+    let exe = std::env::current_exe().map_err(|e| format!("Cannot locate directiv: {e}"))?;
+    let mut sink = format!(
+        "{} --pipe-log {} --max-bytes {} --max-files {}",
+        shell_quote(&exe.to_string_lossy()),
+        shell_quote(&dir.to_string_lossy()),
+        config.max_file_mb.max(1) * 1024 * 1024,
+        config.max_files,
+    );
+    if record {
+        sink.push_str(&format!(" --cast {}", pane_size(app, pane).await?));
+    }
+
+    let output = tmux_command(app)
+        .args(["pipe-pane", "-t", pane, &sink])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run tmux: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("tmux pipe-pane failed: {stderr}"));
+    }
+    Ok(())
+}
+
+/// Files of a task with `extension`, newest first.
+fn list_files(dir: &Path, extension: &str) -> Vec<LogFile> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut logs: Vec<LogFile> = entries
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == extension))
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            Some(LogFile {
+                name: e.file_name().to_string_lossy().to_string(),
+                size: meta.len(),
+                modified: meta
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default(),
+            })
+        })
+        .collect();
+    logs.sort_by(|a, b| b.name.cmp(&a.name));
+    logs
+}
+
+/// Logs of a task, newest first.
+#[tauri::command]
+pub async fn task_logs_list(repo_path: String, task: String) -> Result<Vec<LogFile>, String> {
+    Ok(list_files(&task_log_dir(&repo_path, &task)?, "log"))
+}
+
+/// A page of `limit` lines (default 500) of a log, starting at line `offset`.
+#[tauri::command]
+pub async fn task_log_read(
+    repo_path: String,
+    task: String,
+    name: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<LogPage, String> {
+    let path = log_path(&repo_path, &task, &name, "log")?;
+    let bytes = fs::read(&path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let content = String::from_utf8_lossy(&bytes);
+
+    let offset = offset.unwrap_or(0);
+    let limit = limit.unwrap_or(500).max(1);
+    let total_lines = content.lines().count();
+    let lines: Vec<String> = content
+        .lines()
+        .skip(offset)
+        .take(limit)
+        .map(str::to_string)
+        .collect();
+    let end = offset + lines.len();
+
+    Ok(LogPage {
+        lines,
+        offset,
+        total_lines,
+        next_offset: (end < total_lines).then_some(end),
+    })
+}
+
+/// Delete one log of a task, or all of them when `name` is omitted.
+/// Returns the number of logs deleted.
+#[tauri::command]
+pub async fn task_logs_delete(
+    repo_path: String,
+    task: String,
+    name: Option<String>,
+) -> Result<usize, String> {
+    if let Some(name) = name {
+        let path = log_path(&repo_path, &task, &name, "log")?;
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete {}: {e}", path.display()))?;
+        return Ok(1);
+    }
+
+    let logs = task_logs_list(repo_path.clone(), task.clone()).await?;
+    for log in &logs {
+        let path = log_path(&repo_path, &task, &log.name, "log")?;
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete {}: {e}", path.display()))?;
+    }
+    Ok(logs.len())
+}
+
+/// Time of the last event of a cast, read from the end of the file.
+fn cast_duration(path: &Path) -> Option<f64> {
+    let mut file = fs::File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(4096))).ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).ok()?;
+    String::from_utf8_lossy(&tail)
+        .lines()
+        .rev()
+        .find_map(|line| serde_json::from_str::<(f64, String, String)>(line).ok())
+        .map(|(seconds, _, _)| seconds)
+}
+
+/// Recordings of a task, newest first.
+#[tauri::command]
+pub async fn task_recordings_list(
+    repo_path: String,
+    task: String,
+) -> Result<Vec<Recording>, String> {
+    let dir = task_log_dir(&repo_path, &task)?;
+    Ok(list_files(&dir, "cast")
+        .into_iter()
+        .map(|file| Recording {
+            duration: cast_duration(&dir.join(&file.name)),
+            file,
+        })
+        .collect())
+}
+
+/// Write the part of a recording between `start` and `end` seconds (the end of
+/// the recording by default) to `destination`, or to the task's `exports`
+/// directory. Returns the path written.
+#[tauri::command]
+pub async fn task_recording_export(
+    repo_path: String,
+    task: String,
+    name: String,
+    start: f64,
+    end: Option<f64>,
+    destination: Option<String>,
+) -> Result<String, String> {
+    if end.is_some_and(|end| end < start) {
+        return Err("The end of the segment is before its start".to_string());
+    }
+    let path = log_path(&repo_path, &task, &name, "cast")?;
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let cast = parse_cast(&content).ok_or_else(|| format!("{name} is not an asciicast v2 file"))?;
+
+    let destination = match destination {
+        Some(destination) => PathBuf::from(destination),
+        None => {
+            let dir = task_log_dir(&repo_path, &task)?.join("exports");
+            fs::create_dir_all(&dir)
+                .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+            let stem = name.trim_end_matches(".cast");
+            let end = end.map_or("end".to_string(), |end| format!("{end:.0}"));
+            dir.join(format!("{stem}-{start:.0}-{end}.cast"))
+        }
+    };
+    fs::write(&destination, slice(&cast, start, end))
+        .map_err(|e| format!("Failed to write {}: {e}", destination.display()))?;
+    Ok(destination.to_string_lossy().to_string())
+}
+
+/// Start or stop recording the agent pane of `session`. Its log carries on
+/// either way, and a session that wasn't logged is from now on.
+#[tauri::command]
+pub async fn tmux_set_recording(
+    app: tauri::AppHandle,
+    session: String,
+    record: bool,
+) -> Result<(), String> {
+    let found = directiv_sessions(&app)
+        .await?
+        .into_iter()
+        .find(|s| s.name == session)
+        .ok_or_else(|| format!("No directiv session named {session}"))?;
+    let pane = found
+        .agent_pane
+        .ok_or_else(|| format!("{session} has no agent pane"))?;
+    let task = found.issue.as_deref().unwrap_or(&found.name);
+    let repo_path = found.repo.as_deref().unwrap_or_default();
+    start_pane_log(
+        &app,
+        &pane,
+        repo_path,
+        task,
+        &read_app_config().logs,
+        record,
+    )
+    .await
+}
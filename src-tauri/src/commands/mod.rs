@@ -1,7 +1,24 @@
+pub mod agent_hooks;
+pub mod broadcast;
+pub mod capture_all;
 pub mod config;
+pub mod gc;
 pub mod hooks;
+pub mod layout;
+pub mod logs;
+pub mod pool;
+pub mod pty;
+pub mod readiness;
+pub mod reaper;
+pub mod repair;
+pub mod resources;
+pub mod restore;
+pub mod search;
 pub mod skills;
+pub mod stack;
 pub mod terminal;
 pub mod tmux;
+pub mod tmux_control;
+pub mod web;
 pub mod workspace;
 pub mod worktree;
@@ -0,0 +1,380 @@
+use super::hooks::run_hooks;
+use super::worktree::{
+    copy_path, detect_default_branch, parse_worktree_porcelain, validate_relative_path,
+    worktrees_base,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+use tauri_plugin_shell::process::Output;
+use tauri_plugin_shell::ShellExt;
+
+/// How often pooled worktrees are moved to the latest default branch.
+const POOL_REFRESH_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Per-repo pools of pre-created, detached worktrees at the tip of the default branch.
+///
+/// Pools are declared with `poolSize` in `.directiv.json` and registered when the
+/// workspace is scanned. `worktree_create` claims an entry (checkout -b + move) when
+/// one is ready, which skips the checkout and onStart installs.
+#[derive(Default)]
+pub struct WorktreePool(Mutex<HashMap<String, RepoPool>>);
+
+#[derive(Default)]
+struct RepoPool {
+    size: usize,
+    copy_paths: Vec<String>,
+    on_start: Vec<String>,
+    /// The repo's `fetchBefore`: whether refreshes fetch origin first
+    fetch_before: bool,
+    /// Entries that are up to date and can be claimed
+    ready: Vec<PathBuf>,
+    /// Set while a refresh owns the pool's entries
+    refreshing: bool,
+}
+
+/// `<repo>-worktrees/.pool` — never collides with a task dir, which cannot start with '.'
+pub(crate) fn pool_dir(worktrees_base: &Path) -> PathBuf {
+    worktrees_base.join(".pool")
+}
+
+async fn run_git(app: &tauri::AppHandle, args: &[&str]) -> Result<Output, String> {
+    app.shell()
+        .command("git")
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {e}"))
+}
+
+async fn git_ok(app: &tauri::AppHandle, args: &[&str]) -> Result<(), String> {
+    let out = run_git(app, args).await?;
+    if out.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "git {} failed: {}",
+            args.get(2).unwrap_or(&""),
+            String::from_utf8_lossy(&out.stderr).trim()
+        ))
+    }
+}
+
+/// Register (or update) a repo's pool and refill it in the background.
+pub(crate) fn register_repo_pool(
+    app: &tauri::AppHandle,
+    repo_path: &str,
+    size: usize,
+    copy_paths: &[String],
+    on_start: &[String],
+    fetch_before: bool,
+) {
+    let pool = app.state::<WorktreePool>();
+    {
+        let mut pools = pool.0.lock().unwrap();
+        if size == 0 && !pools.contains_key(repo_path) {
+            return;
+        }
+        let entry = pools.entry(repo_path.to_string()).or_default();
+        let unchanged = entry.size == size
+            && entry.copy_paths == copy_paths
+            && entry.on_start == on_start
+            && entry.fetch_before == fetch_before
+            && entry.ready.len() == size;
+        entry.fetch_before = fetch_before;
+        entry.size = size;
+        entry.copy_paths = copy_paths.to_vec();
+        entry.on_start = on_start.to_vec();
+        if unchanged {
+            return;
+        }
+    }
+
+    let app = app.clone();
+    let repo_path = repo_path.to_string();
+    tauri::async_runtime::spawn(async move {
+        refresh_repo_pool(&app, &repo_path).await;
+    });
+}
+
+/// Forget the pool's entry paths after worktrees were moved, and drop pools of repos
+/// that no longer exist. Entries are re-adopted from `git worktree list` on refresh.
+pub(crate) fn reset_repo_pool(app: &tauri::AppHandle, repo_path: &str) {
+    {
+        let pool = app.state::<WorktreePool>();
+        let mut pools = pool.0.lock().unwrap();
+        pools.retain(|repo, _| Path::new(repo).exists());
+        match pools.get_mut(repo_path) {
+            Some(p) if !p.refreshing => p.ready.clear(),
+            _ => return,
+        }
+    }
+
+    let app = app.clone();
+    let repo_path = repo_path.to_string();
+    tauri::async_runtime::spawn(async move {
+        refresh_repo_pool(&app, &repo_path).await;
+    });
+}
+
+/// Refresh every registered pool periodically for the lifetime of the app.
+pub fn spawn_pool_refresher(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(POOL_REFRESH_INTERVAL).await;
+            let repos: Vec<String> = {
+                let pool = app.state::<WorktreePool>();
+                let pools = pool.0.lock().unwrap();
+                pools.keys().cloned().collect()
+            };
+            for repo_path in repos {
+                refresh_repo_pool(&app, &repo_path).await;
+            }
+        }
+    });
+}
+
+/// Take a ready entry, turn it into `branch` (from `base`) and move it to `dest`.
+///
+/// Returns false when the pool is empty or the claim fails, in which case the caller
+/// creates the worktree the normal way.
+pub(crate) async fn claim_pooled_worktree(
+    app: &tauri::AppHandle,
+    repo_path: &str,
+    branch: &str,
+    base: &str,
+    dest: &Path,
+) -> bool {
+    let entry = {
+        let pool = app.state::<WorktreePool>();
+        let mut pools = pool.0.lock().unwrap();
+        pools.get_mut(repo_path).and_then(|p| p.ready.pop())
+    };
+    let Some(entry) = entry else {
+        return false;
+    };
+    let entry_str = entry.to_string_lossy().to_string();
+    let dest_str = dest.to_string_lossy().to_string();
+
+    let checkout = [
+        "-C",
+        &entry_str,
+        "checkout",
+        "--no-track",
+        "-b",
+        branch,
+        base,
+    ];
+    let claimed = match git_ok(app, &checkout).await {
+        Ok(()) => {
+            match git_ok(
+                app,
+                &["-C", repo_path, "worktree", "move", &entry_str, &dest_str],
+            )
+            .await
+            {
+                Ok(()) => true,
+                Err(e) => {
+                    log::warn!("Pool claim for {branch} failed (falling back): {e}");
+                    // Put the entry back as it was so it can be reused
+                    let _ = git_ok(app, &["-C", &entry_str, "checkout", "--detach"]).await;
+                    let _ = git_ok(app, &["-C", repo_path, "branch", "-D", branch]).await;
+                    false
+                }
+            }
+        }
+        Err(e) => {
+            log::warn!("Pool claim for {branch} failed (falling back): {e}");
+            false
+        }
+    };
+
+    if claimed {
+        log::info!("Claimed pooled worktree for {branch}");
+    } else {
+        let pool = app.state::<WorktreePool>();
+        let mut pools = pool.0.lock().unwrap();
+        if let Some(p) = pools.get_mut(repo_path) {
+            p.ready.push(entry);
+        }
+        return false;
+    }
+
+    // Top the pool back up for the next task
+    let app = app.clone();
+    let repo_path = repo_path.to_string();
+    tauri::async_runtime::spawn(async move {
+        refresh_repo_pool(&app, &repo_path).await;
+    });
+    true
+}
+
+/// Bring a repo's pool to its configured size, with every entry at the latest default branch.
+async fn refresh_repo_pool(app: &tauri::AppHandle, repo_path: &str) {
+    // Take ownership of the ready entries so they cannot be claimed mid-refresh
+    let (size, copy_paths, on_start, fetch_before, mut entries) = {
+        let pool = app.state::<WorktreePool>();
+        let mut pools = pool.0.lock().unwrap();
+        let Some(p) = pools.get_mut(repo_path) else {
+            return;
+        };
+        if p.refreshing {
+            return;
+        }
+        p.refreshing = true;
+        (
+            p.size,
+            p.copy_paths.clone(),
+            p.on_start.clone(),
+            p.fetch_before,
+            std::mem::take(&mut p.ready),
+        )
+    };
+
+    let refreshed = refresh_entries(
+        app,
+        repo_path,
+        size,
+        &copy_paths,
+        &on_start,
+        fetch_before,
+        &mut entries,
+    )
+    .await;
+    if let Err(e) = refreshed {
+        log::warn!("Worktree pool refresh for {repo_path} failed: {e}");
+    }
+
+    let pool = app.state::<WorktreePool>();
+    let mut pools = pool.0.lock().unwrap();
+    if let Some(p) = pools.get_mut(repo_path) {
+        p.refreshing = false;
+        p.ready.extend(entries);
+    }
+}
+
+async fn refresh_entries(
+    app: &tauri::AppHandle,
+    repo_path: &str,
+    size: usize,
+    copy_paths: &[String],
+    on_start: &[String],
+    fetch_before: bool,
+    entries: &mut Vec<PathBuf>,
+) -> Result<(), String> {
+    let repo = Path::new(repo_path);
+    let dir = pool_dir(&worktrees_base(repo)?);
+
+    if fetch_before {
+        if let Err(e) = git_ok(app, &["-C", repo_path, "fetch", "origin"]).await {
+            log::warn!("Pool fetch failed (continuing): {e}");
+        }
+    }
+    let _ = git_ok(app, &["-C", repo_path, "worktree", "prune"]).await;
+
+    let default_branch = detect_default_branch(app, repo_path).await;
+    let target = run_git(app, &["-C", repo_path, "rev-parse", &default_branch]).await?;
+    if !target.status.success() {
+        return Err(format!("Cannot resolve {default_branch}"));
+    }
+    let target = String::from_utf8_lossy(&target.stdout).trim().to_string();
+
+    // Adopt pool worktrees left on disk by a previous run of the app
+    let list = run_git(app, &["-C", repo_path, "worktree", "list", "--porcelain"]).await?;
+    for wt in parse_worktree_porcelain(&String::from_utf8_lossy(&list.stdout)) {
+        let path = PathBuf::from(&wt.path);
+        if path.starts_with(&dir) && wt.branch.is_empty() && !entries.contains(&path) {
+            entries.push(path);
+        }
+    }
+
+    // Drop entries beyond the configured size
+    while entries.len() > size {
+        if let Some(extra) = entries.pop() {
+            remove_entry(app, repo_path, &extra).await;
+        }
+    }
+
+    // Move existing entries to the latest default branch, reinstalling if it moved
+    let mut fresh = Vec::new();
+    for entry in entries.drain(..) {
+        let entry_str = entry.to_string_lossy().to_string();
+        let head = run_git(app, &["-C", &entry_str, "rev-parse", "HEAD"])
+            .await
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+            .unwrap_or_default();
+        if head == target {
+            fresh.push(entry);
+            continue;
+        }
+        let updated = async {
+            git_ok(app, &["-C", &entry_str, "reset", "--hard"]).await?;
+            git_ok(app, &["-C", &entry_str, "checkout", "--detach", &target]).await?;
+            run_hooks(app.clone(), on_start.to_vec(), entry_str.clone()).await
+        }
+        .await;
+        match updated {
+            Ok(()) => fresh.push(entry),
+            Err(e) => {
+                log::warn!("Dropping pooled worktree {entry_str}: {e}");
+                remove_entry(app, repo_path, &entry).await;
+            }
+        }
+    }
+    *entries = fresh;
+
+    // Create missing entries
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create pool directory {}: {e}", dir.display()))?;
+    while entries.len() < size {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let entry = dir.join(format!("wt-{nanos}"));
+        let entry_str = entry.to_string_lossy().to_string();
+
+        git_ok(
+            app,
+            &[
+                "-C", repo_path, "worktree", "add", "--detach", &entry_str, &target,
+            ],
+        )
+        .await?;
+
+        let prepared = async {
+            for rel in copy_paths {
+                validate_relative_path(rel)?;
+                let src = repo.join(rel);
+                if src.exists() {
+                    copy_path(&src, &entry.join(rel))?;
+                }
+            }
+            run_hooks(app.clone(), on_start.to_vec(), entry_str.clone()).await
+        }
+        .await;
+        if let Err(e) = prepared {
+            remove_entry(app, repo_path, &entry).await;
+            return Err(format!("Failed to prepare pooled worktree: {e}"));
+        }
+
+        log::info!("Added pooled worktree {entry_str}");
+        entries.push(entry);
+    }
+
+    Ok(())
+}
+
+async fn remove_entry(app: &tauri::AppHandle, repo_path: &str, entry: &Path) {
+    let entry_str = entry.to_string_lossy().to_string();
+    if let Err(e) = git_ok(
+        app,
+        &["-C", repo_path, "worktree", "remove", "--force", &entry_str],
+    )
+    .await
+    {
+        log::warn!("Failed to remove pooled worktree {entry_str}: {e}");
+    }
+}
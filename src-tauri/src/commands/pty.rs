@@ -0,0 +1,187 @@
+use super::tmux::socket_name;
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{Emitter, Manager};
+
+/// Terminals embedded in the app, each attached to a tmux session through a PTY.
+///
+/// Output is emitted as Tauri events:
+///
+/// - `pty://output` — [`PtyOutputEvent`]
+/// - `pty://exit` — [`PtyExitEvent`], the attach client ended
+#[derive(Default)]
+pub struct PtySessions {
+    next_id: AtomicU32,
+    ptys: Mutex<HashMap<u32, Pty>>,
+}
+
+struct Pty {
+    master: Box<dyn MasterPty + Send>,
+    /// Shared so writes happen without holding the lock on every terminal
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    child: Box<dyn Child + Send + Sync>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PtyOutputEvent {
+    pub id: u32,
+    pub data: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PtyExitEvent {
+    pub id: u32,
+    pub code: Option<u32>,
+}
+
+fn size(cols: u16, rows: u16) -> PtySize {
+    PtySize {
+        rows: rows.max(1),
+        cols: cols.max(1),
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+/// Length of the longest prefix of `bytes` that doesn't end inside a UTF-8
+/// character, so a character split across reads is emitted whole.
+fn complete_utf8_len(bytes: &[u8]) -> usize {
+    match std::str::from_utf8(bytes) {
+        Ok(_) => bytes.len(),
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => bytes.len(),
+    }
+}
+
+fn read_output(app: tauri::AppHandle, id: u32, mut reader: Box<dyn Read + Send>) {
+    let mut buf = [0u8; 8192];
+    let mut pending: Vec<u8> = Vec::new();
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                pending.extend_from_slice(&buf[..n]);
+                let complete = complete_utf8_len(&pending);
+                let data = String::from_utf8_lossy(&pending[..complete]).to_string();
+                pending.drain(..complete);
+                let _ = app.emit("pty://output", PtyOutputEvent { id, data });
+            }
+        }
+    }
+
+    let pty = app.state::<PtySessions>().ptys.lock().unwrap().remove(&id);
+    let code = pty.and_then(|mut pty| pty.child.wait().ok().map(|s| s.exit_code()));
+    let _ = app.emit("pty://exit", PtyExitEvent { id, code });
+}
+
+/// Attach `session` in a new PTY of `cols`×`rows`. Returns the terminal's id.
+#[tauri::command]
+pub async fn pty_open(
+    app: tauri::AppHandle,
+    session: String,
+    cols: u16,
+    rows: u16,
+) -> Result<u32, String> {
+    let pair = native_pty_system()
+        .openpty(size(cols, rows))
+        .map_err(|e| format!("Failed to open a PTY: {e}"))?;
+
+    let mut command = CommandBuilder::new("tmux");
+    if let Some(name) = socket_name(&app) {
+        command.args(["-L", &name]);
+    }
+    command.args(["attach-session", "-t", &format!("={session}")]);
+    command.env("TERM", "xterm-256color");
+    // Started from inside tmux, the attach would refuse to nest
+    command.env_remove("TMUX");
+    if let Some(home) = dirs::home_dir() {
+        command.cwd(home);
+    }
+
+    let child = pair
+        .slave
+        .spawn_command(command)
+        .map_err(|e| format!("Failed to attach {session}: {e}"))?;
+    // The child holds its own handle; ours would keep the PTY open after it exits
+    drop(pair.slave);
+
+    let reader = pair
+        .master
+        .try_clone_reader()
+        .map_err(|e| format!("Failed to read the PTY: {e}"))?;
+    let writer = pair
+        .master
+        .take_writer()
+        .map_err(|e| format!("Failed to write the PTY: {e}"))?;
+
+    let sessions = app.state::<PtySessions>();
+    let id = sessions.next_id.fetch_add(1, Ordering::Relaxed);
+    sessions.ptys.lock().unwrap().insert(
+        id,
+        Pty {
+            master: pair.master,
+            writer: Arc::new(Mutex::new(writer)),
+            child,
+        },
+    );
+
+    let app = app.clone();
+    std::thread::spawn(move || read_output(app, id, reader));
+    Ok(id)
+}
+
+/// Send keyboard input to a terminal.
+#[tauri::command]
+pub async fn pty_write(app: tauri::AppHandle, id: u32, data: String) -> Result<(), String> {
+    let writer = {
+        let sessions = app.state::<PtySessions>();
+        let ptys = sessions.ptys.lock().unwrap();
+        let pty = ptys.get(&id).ok_or(format!("No terminal {id}"))?;
+        pty.writer.clone()
+    };
+    // A terminal that stops reading would block the write
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut writer = writer.lock().unwrap();
+        writer
+            .write_all(data.as_bytes())
+            .and_then(|_| writer.flush())
+    })
+    .await
+    .map_err(|e| format!("Failed to write to terminal {id}: {e}"))?
+    .map_err(|e| format!("Failed to write to terminal {id}: {e}"))
+}
+
+#[tauri::command]
+pub async fn pty_resize(
+    app: tauri::AppHandle,
+    id: u32,
+    cols: u16,
+    rows: u16,
+) -> Result<(), String> {
+    let sessions = app.state::<PtySessions>();
+    let ptys = sessions.ptys.lock().unwrap();
+    let pty = ptys.get(&id).ok_or(format!("No terminal {id}"))?;
+    pty.master
+        .resize(size(cols, rows))
+        .map_err(|e| format!("Failed to resize terminal {id}: {e}"))
+}
+
+/// Detach and close a terminal; the tmux session keeps running.
+#[tauri::command]
+pub async fn pty_close(app: tauri::AppHandle, id: u32) -> Result<(), String> {
+    let sessions = app.state::<PtySessions>();
+    let Some(mut pty) = sessions.ptys.lock().unwrap().remove(&id) else {
+        return Ok(());
+    };
+    pty.child
+        .kill()
+        .map_err(|e| format!("Failed to close terminal {id}: {e}"))?;
+    let _ = pty.child.wait();
+    Ok(())
+}
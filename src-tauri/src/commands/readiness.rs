@@ -0,0 +1,317 @@
+use super::resources::process_table;
+use super::tmux::{capture, tmux_command, tmux_list_sessions, CaptureOptions};
+use crate::agent_state::{detect, AgentState};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tauri_plugin_shell::process::CommandEvent;
+
+/// How often a stage re-checks the pane.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Lines of the pane included in a diagnostic.
+const DIAGNOSTIC_LINES: usize = 15;
+
+/// Stages of a session becoming usable, in order. The agent command is typed
+/// between `Shell` and `Process`.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReadyStage {
+    /// The shell finished its rc files and signalled `ready_<session>`
+    Shell,
+    /// The agent process runs in the agent pane
+    Process,
+    /// The agent's UI is on screen
+    Prompt,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReadyOptions {
+    pub stages: Vec<ReadyStage>,
+    pub shell_timeout_ms: u64,
+    pub process_timeout_ms: u64,
+    pub prompt_timeout_ms: u64,
+    /// Process names that count as the agent; defaults to the session's agent
+    pub process_names: Vec<String>,
+    /// Regex marking the prompt; defaults to recognizing the agent's UI
+    pub prompt_pattern: Option<String>,
+}
+
+impl Default for ReadyOptions {
+    fn default() -> Self {
+        Self {
+            stages: vec![ReadyStage::Shell],
+            shell_timeout_ms: 30_000,
+            process_timeout_ms: 20_000,
+            prompt_timeout_ms: 30_000,
+            process_names: Vec::new(),
+            prompt_pattern: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StageReport {
+    pub stage: ReadyStage,
+    pub elapsed_ms: u64,
+}
+
+/// Why a stage failed, with the state of the pane at that point.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadyDiagnostic {
+    pub stage: ReadyStage,
+    pub reason: String,
+    pub elapsed_ms: u64,
+    /// `pane_current_command`, `None` once the pane is gone
+    pub pane_command: Option<String>,
+    /// Last non-empty lines of the pane
+    pub tail: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadyReport {
+    pub ready: bool,
+    /// Stages passed, in order
+    pub stages: Vec<StageReport>,
+    pub diagnostic: Option<ReadyDiagnostic>,
+}
+
+struct PaneInfo {
+    pid: u32,
+    command: String,
+}
+
+/// `None` once the pane (or its session) is gone.
+async fn pane_info(app: &tauri::AppHandle, target: &str) -> Option<PaneInfo> {
+    let output = tmux_command(app)
+        .args([
+            "display-message",
+            "-p",
+            "-t",
+            target,
+            "#{pane_dead}|#{pane_pid}|#{pane_current_command}",
+        ])
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut parts = stdout.trim_end().splitn(3, '|');
+    if parts.next()? == "1" {
+        return None;
+    }
+    Some(PaneInfo {
+        pid: parts.next()?.parse().ok()?,
+        command: parts.next().unwrap_or_default().to_string(),
+    })
+}
+
+async fn diagnose(
+    app: &tauri::AppHandle,
+    session: &str,
+    pane: Option<&str>,
+    stage: ReadyStage,
+    reason: String,
+    started: Instant,
+) -> ReadyDiagnostic {
+    let target = pane.map_or_else(|| format!("={session}:"), str::to_string);
+    let pane_command = pane_info(app, &target).await.map(|info| info.command);
+    let tail = match capture(app, session, pane, &CaptureOptions::default()).await {
+        Ok(screen) => {
+            let lines: Vec<String> = screen
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .map(str::to_string)
+                .collect();
+            lines[lines.len().saturating_sub(DIAGNOSTIC_LINES)..].to_vec()
+        }
+        Err(_) => Vec::new(),
+    };
+    ReadyDiagnostic {
+        stage,
+        reason,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        pane_command,
+        tail,
+    }
+}
+
+/// Wait for the shell's `ready_<session>` signal, failing early when the session
+/// goes away (e.g. the shell exited on an rc error) rather than at the timeout.
+async fn wait_shell(app: &tauri::AppHandle, session: &str, limit: Duration) -> Result<(), String> {
+    let signal = format!("ready_{session}");
+    let (mut rx, child) = tmux_command(app)
+        .args(["wait-for", &signal])
+        .spawn()
+        .map_err(|e| format!("Failed to spawn tmux wait-for: {e}"))?;
+
+    let deadline = tokio::time::sleep(limit);
+    tokio::pin!(deadline);
+    let mut poll = tokio::time::interval(POLL_INTERVAL);
+    let target = format!("={session}:");
+    let result = loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Some(CommandEvent::Terminated(status)) if status.code == Some(0) => break Ok(()),
+                Some(CommandEvent::Terminated(status)) => {
+                    break Err(format!("tmux wait-for exited with code {:?}", status.code))
+                }
+                Some(_) => {}
+                None => break Err("tmux wait-for process ended unexpectedly".to_string()),
+            },
+            _ = poll.tick() => {
+                if pane_info(app, &target).await.is_none() {
+                    break Err("The session's shell exited before signalling readiness".to_string());
+                }
+            }
+            _ = &mut deadline => {
+                break Err(format!("Shell init timed out after {}ms", limit.as_millis()));
+            }
+        }
+    };
+    if result.is_err() {
+        let _ = child.kill();
+    }
+    result
+}
+
+async fn wait_process(
+    app: &tauri::AppHandle,
+    pane: &str,
+    names: &[String],
+    limit: Duration,
+) -> Result<(), String> {
+    let started = Instant::now();
+    loop {
+        let Some(info) = pane_info(app, pane).await else {
+            return Err("The agent pane is gone".to_string());
+        };
+        if names.contains(&info.command) {
+            return Ok(());
+        }
+        let table = process_table(app, false).await;
+        let is_agent = |pid| {
+            table
+                .name(pid)
+                .is_some_and(|n| names.iter().any(|m| m == n))
+        };
+        if table.descendants(info.pid).into_iter().any(is_agent) {
+            return Ok(());
+        }
+        if started.elapsed() >= limit {
+            return Err(format!(
+                "No {} process started within {}ms",
+                names.join("/"),
+                limit.as_millis()
+            ));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn wait_prompt(
+    app: &tauri::AppHandle,
+    session: &str,
+    pane: Option<&str>,
+    pattern: Option<&Regex>,
+    limit: Duration,
+) -> Result<(), String> {
+    let started = Instant::now();
+    loop {
+        let screen = capture(app, session, pane, &CaptureOptions::default())
+            .await
+            .map_err(|_| "The agent pane is gone".to_string())?;
+        let visible = match pattern {
+            Some(pattern) => pattern.is_match(&screen),
+            None => detect(&screen).state != AgentState::Unknown,
+        };
+        if visible {
+            return Ok(());
+        }
+        if started.elapsed() >= limit {
+            return Err(format!(
+                "The agent prompt did not appear within {}ms",
+                limit.as_millis()
+            ));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Run the requested readiness stages (only `shell` by default), each under its own
+/// timeout. A failed stage stops the run and is described in `diagnostic`.
+#[tauri::command]
+pub async fn tmux_wait_for_ready(
+    app: tauri::AppHandle,
+    session: String,
+    options: Option<ReadyOptions>,
+) -> Result<ReadyReport, String> {
+    let options = options.unwrap_or_default();
+    let prompt_pattern = options
+        .prompt_pattern
+        .as_deref()
+        .map(Regex::new)
+        .transpose()
+        .map_err(|e| format!("Invalid prompt pattern: {e}"))?;
+
+    // The agent stages look at the agent's pane, not whichever pane is active
+    let listed = tmux_list_sessions(app.clone())
+        .await?
+        .into_iter()
+        .find(|s| s.name == session);
+    let agent_pane = listed.as_ref().and_then(|s| s.agent_pane.clone());
+    let names = if options.process_names.is_empty() {
+        let agent = listed.and_then(|s| s.agent);
+        vec![agent.unwrap_or_else(|| "claude".to_string())]
+    } else {
+        options.process_names.clone()
+    };
+    let pane_target = agent_pane.clone().unwrap_or_else(|| format!("={session}:"));
+
+    let mut report = ReadyReport {
+        ready: true,
+        stages: Vec::new(),
+        diagnostic: None,
+    };
+    for stage in options.stages {
+        let started = Instant::now();
+        let result = match stage {
+            ReadyStage::Shell => {
+                wait_shell(
+                    &app,
+                    &session,
+                    Duration::from_millis(options.shell_timeout_ms),
+                )
+                .await
+            }
+            ReadyStage::Process => {
+                let limit = Duration::from_millis(options.process_timeout_ms);
+                wait_process(&app, &pane_target, &names, limit).await
+            }
+            ReadyStage::Prompt => {
+                let limit = Duration::from_millis(options.prompt_timeout_ms);
+                let pattern = prompt_pattern.as_ref();
+                wait_prompt(&app, &session, agent_pane.as_deref(), pattern, limit).await
+            }
+        };
+        match result {
+            Ok(()) => report.stages.push(StageReport {
+                stage,
+                elapsed_ms: started.elapsed().as_millis() as u64,
+            }),
+            Err(reason) => {
+                let pane = agent_pane.as_deref();
+                report.ready = false;
+                report.diagnostic =
+                    Some(diagnose(&app, &session, pane, stage, reason, started).await);
+                break;
+            }
+        }
+    }
+    Ok(report)
+}
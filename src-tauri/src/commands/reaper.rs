@@ -0,0 +1,436 @@
+use super::agent_hooks::{AgentHooks, HookState};
+use super::config::{directiv_home, read_app_config};
+use super::gc::{now_secs, worktree_dirty};
+use super::logs::task_log_dir;
+use super::tmux::{
+    capture, directiv_panes, directiv_sessions, tmux_command, tmux_kill_session, CaptureOptions,
+    TmuxSession,
+};
+use super::worktree::worktree_remove;
+use crate::agent_state::{detect, AgentState};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+/// `sessionReaper` in `directiv.config.json`.
+///
+/// A session is idle when neither tmux (input or output in any of its windows) nor
+/// the agent's hooks saw activity for `idleAfterMinutes`. Sessions whose agent is
+/// working or whose worktree has uncommitted changes are never touched.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SessionReaperConfig {
+    /// Run the reaper in the background (previews work regardless)
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    pub idle_after_minutes: u64,
+    /// What to do with an idle session
+    pub action: ReapAction,
+}
+
+impl Default for SessionReaperConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: 15,
+            idle_after_minutes: 240,
+            action: ReapAction::Notify,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReapAction {
+    Keep,
+    /// Emit `session://idle`, once per idle period
+    Notify,
+    /// Detach every client
+    Detach,
+    /// Save the scrollback of every pane to the task's logs, then kill the session
+    Kill,
+    /// Kill as above, then remove the worktree and keep its branch
+    Archive,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReapDecision {
+    pub session: String,
+    pub issue: Option<String>,
+    pub worktree: Option<String>,
+    pub action: ReapAction,
+    pub reason: String,
+    pub idle_minutes: u64,
+    /// False in preview mode, or when the action failed
+    pub applied: bool,
+    pub error: Option<String>,
+    /// Unix seconds
+    pub timestamp: u64,
+}
+
+/// Last activity each session was notified about, so a session idle across many
+/// runs is only reported once.
+#[derive(Default)]
+pub struct SessionReaper(Mutex<HashMap<String, u64>>);
+
+fn reaper_log_path() -> Result<PathBuf, String> {
+    Ok(directiv_home()?.join("session-reaper.log"))
+}
+
+fn append_reaper_log(decisions: &[ReapDecision]) -> Result<(), String> {
+    let path = reaper_log_path()?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    for decision in decisions {
+        let line = serde_json::to_string(decision).map_err(|e| e.to_string())?;
+        writeln!(file, "{line}").map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Latest of `session_activity` and each window's `window_activity`, by session,
+/// from lines of `session|session_activity|window_activity`. tmux keeps no
+/// per-pane timestamp; output in any pane updates its window's.
+fn parse_activity(stdout: &str) -> HashMap<String, u64> {
+    let mut latest: HashMap<String, u64> = HashMap::new();
+    for line in stdout.lines() {
+        let mut parts = line.rsplitn(3, '|');
+        let (Some(window), Some(session_activity), Some(name)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let activity = [window, session_activity]
+            .iter()
+            .filter_map(|t| t.parse::<u64>().ok())
+            .max()
+            .unwrap_or(0);
+        let entry = latest.entry(name.to_string()).or_default();
+        *entry = (*entry).max(activity);
+    }
+    latest
+}
+
+async fn tmux_activity(app: &tauri::AppHandle) -> Result<HashMap<String, u64>, String> {
+    let output = tmux_command(app)
+        .args([
+            "list-windows",
+            "-a",
+            "-F",
+            "#{session_name}|#{session_activity}|#{window_activity}",
+        ])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run tmux: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("tmux list-windows failed: {stderr}"));
+    }
+    Ok(parse_activity(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Write the full history of every pane of `session` to a new log of its task.
+async fn save_scrollback(app: &tauri::AppHandle, session: &TmuxSession) -> Result<(), String> {
+    let history = CaptureOptions {
+        start: Some("-".to_string()),
+        join: true,
+        ..Default::default()
+    };
+    let mut text = String::new();
+    for pane in directiv_panes(app).await? {
+        if pane.session != session.name {
+            continue;
+        }
+        let scrollback = capture(app, &session.name, Some(&pane.id), &history).await?;
+        text.push_str(&format!(
+            "===== {}:{} {} ({}) =====\n",
+            pane.window_index, pane.window_name, pane.id, pane.command
+        ));
+        text.push_str(scrollback.trim_end());
+        text.push_str("\n\n");
+    }
+
+    let task = session.issue.as_deref().unwrap_or(&session.name);
+    let dir = task_log_dir(session.repo.as_deref().unwrap_or_default(), task)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    let path = dir.join(format!("{}.log", now_secs() * 1000));
+    fs::write(&path, text).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+async fn apply(
+    app: &tauri::AppHandle,
+    session: &TmuxSession,
+    decision: &ReapDecision,
+) -> Result<(), String> {
+    match decision.action {
+        ReapAction::Keep => Ok(()),
+        ReapAction::Notify => app
+            .emit("session://idle", decision)
+            .map_err(|e| format!("Failed to emit session://idle: {e}")),
+        ReapAction::Detach => {
+            // Not the control-mode client streaming the session's output to the app
+            let output = tmux_command(app)
+                .args([
+                    "list-clients",
+                    "-t",
+                    &format!("={}", session.name),
+                    "-F",
+                    "#{client_name} #{client_control_mode}",
+                ])
+                .output()
+                .await
+                .map_err(|e| format!("Failed to run tmux: {e}"))?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("tmux list-clients failed: {stderr}"));
+            }
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let clients = stdout
+                .lines()
+                .filter_map(|line| line.rsplit_once(' '))
+                .filter(|(_, control)| *control != "1")
+                .map(|(name, _)| name);
+            for client in clients {
+                let output = tmux_command(app)
+                    .args(["detach-client", "-t", client])
+                    .output()
+                    .await
+                    .map_err(|e| format!("Failed to run tmux: {e}"))?;
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    return Err(format!("tmux detach-client failed: {stderr}"));
+                }
+            }
+            Ok(())
+        }
+        ReapAction::Kill | ReapAction::Archive => {
+            // Never lose the history: no log, no kill
+            save_scrollback(app, session)
+                .await
+                .map_err(|e| format!("Failed to save the log: {e}"))?;
+            // Also forgets the saved session, so it isn't restored at the next launch
+            tmux_kill_session(app.clone(), session.name.clone()).await?;
+            if let (ReapAction::Archive, Some(repo), Some(worktree)) =
+                (decision.action, &session.repo, &session.worktree)
+            {
+                worktree_remove(
+                    app.clone(),
+                    repo.clone(),
+                    worktree.clone(),
+                    None,
+                    Some(false),
+                )
+                .await?;
+            }
+            Ok(())
+        }
+    }
+}
+
+async fn agent_working(
+    app: &tauri::AppHandle,
+    session: &TmuxSession,
+    hook: Option<HookState>,
+) -> bool {
+    if let Some(state) = hook {
+        return state == HookState::Working;
+    }
+    match capture(
+        app,
+        &session.name,
+        session.agent_pane.as_deref(),
+        &CaptureOptions::default(),
+    )
+    .await
+    {
+        Ok(screen) => detect(&screen).state == AgentState::Working,
+        Err(_) => false,
+    }
+}
+
+async fn session_dirty(app: &tauri::AppHandle, session: &TmuxSession) -> bool {
+    match &session.worktree {
+        Some(worktree) => worktree_dirty(app, worktree).await,
+        None => false,
+    }
+}
+
+async fn run_reaper(
+    app: &tauri::AppHandle,
+    config: &SessionReaperConfig,
+    preview: bool,
+) -> Result<Vec<ReapDecision>, String> {
+    let sessions = directiv_sessions(app).await?;
+    let activity = tmux_activity(app).await?;
+    let hooks: HashMap<String, (HookState, u64)> = app
+        .state::<AgentHooks>()
+        .states()
+        .into_iter()
+        .map(|s| (s.worktree, (s.state, s.updated / 1000)))
+        .collect();
+    let now = now_secs();
+    let threshold = config.idle_after_minutes.max(1) * 60;
+    let mut decisions = Vec::new();
+
+    for session in sessions {
+        let hook = session
+            .worktree
+            .as_ref()
+            .and_then(|w| hooks.get(w).copied());
+        let last_active = activity
+            .get(&session.name)
+            .copied()
+            .unwrap_or(now)
+            .max(hook.map_or(0, |(_, updated)| updated));
+        let idle_secs = now.saturating_sub(last_active);
+        let mut decision = ReapDecision {
+            session: session.name.clone(),
+            issue: session.issue.clone(),
+            worktree: session.worktree.clone(),
+            action: ReapAction::Keep,
+            reason: String::new(),
+            idle_minutes: idle_secs / 60,
+            applied: false,
+            error: None,
+            timestamp: now,
+        };
+
+        if idle_secs < threshold {
+            decision.reason = format!("Active {} minutes ago", decision.idle_minutes);
+        } else if agent_working(app, &session, hook.map(|(state, _)| state)).await {
+            decision.reason = "Agent is working".to_string();
+        } else if session_dirty(app, &session).await {
+            decision.reason = "Uncommitted changes".to_string();
+        } else {
+            decision.action = config.action;
+            decision.reason = format!("Idle for {} minutes", decision.idle_minutes);
+            match config.action {
+                ReapAction::Detach if !session.attached => {
+                    decision.action = ReapAction::Keep;
+                    decision.reason.push_str(", already detached");
+                }
+                ReapAction::Notify => {
+                    let reaper = app.state::<SessionReaper>();
+                    let notified = reaper.0.lock().unwrap();
+                    if notified.get(&session.name) == Some(&last_active) {
+                        decision.action = ReapAction::Keep;
+                        decision.reason.push_str(", already notified");
+                    }
+                }
+                ReapAction::Archive if session.worktree.is_none() || session.repo.is_none() => {
+                    decision.action = ReapAction::Kill;
+                    decision.reason.push_str(", no worktree to archive");
+                }
+                _ => {}
+            }
+        }
+
+        if !preview && decision.action != ReapAction::Keep {
+            match apply(app, &session, &decision).await {
+                Ok(()) => decision.applied = true,
+                Err(e) => decision.error = Some(e),
+            }
+            if decision.applied && decision.action == ReapAction::Notify {
+                let reaper = app.state::<SessionReaper>();
+                reaper
+                    .0
+                    .lock()
+                    .unwrap()
+                    .insert(session.name.clone(), last_active);
+            }
+        }
+
+        decisions.push(decision);
+    }
+
+    if !preview {
+        let acted: Vec<ReapDecision> = decisions
+            .iter()
+            .filter(|d| d.action != ReapAction::Keep)
+            .cloned()
+            .collect();
+        if !acted.is_empty() {
+            append_reaper_log(&acted)?;
+        }
+    }
+
+    Ok(decisions)
+}
+
+/// Evaluate the reaper policy over every directiv session.
+/// Runs as a preview (nothing done, nothing logged) unless `preview` is `false`.
+#[tauri::command]
+pub async fn session_reaper_run(
+    app: tauri::AppHandle,
+    preview: Option<bool>,
+) -> Result<Vec<ReapDecision>, String> {
+    let config = read_app_config();
+    run_reaper(&app, &config.session_reaper, preview != Some(false)).await
+}
+
+/// Most recent reaper actions, newest first.
+#[tauri::command]
+pub async fn session_reaper_log(limit: Option<usize>) -> Result<Vec<ReapDecision>, String> {
+    let path = reaper_log_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file =
+        fs::File::open(&path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+
+    let mut entries: Vec<ReapDecision> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect();
+    entries.reverse();
+    entries.truncate(limit.unwrap_or(100));
+    Ok(entries)
+}
+
+/// Apply the reaper policy on its configured interval when `sessionReaper.enabled`
+/// is set. The config is re-read every tick so edits apply without a restart.
+pub fn spawn_reaper_scheduler(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = read_app_config();
+            let interval = config.session_reaper.interval_minutes.max(1);
+            tokio::time::sleep(Duration::from_secs(interval * 60)).await;
+
+            let config = read_app_config();
+            if !config.session_reaper.enabled {
+                continue;
+            }
+            match run_reaper(&app, &config.session_reaper, false).await {
+                Ok(decisions) => {
+                    let acted = decisions.iter().filter(|d| d.applied).count();
+                    log::info!("Session reaper: acted on {acted} idle session(s)");
+                }
+                Err(e) => log::warn!("Session reaper failed: {e}"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn activity_is_the_latest_of_session_and_windows() {
+        let stdout = "eng-1|100|90\neng-1|100|250\nweird|name|300|200\nbroken\n";
+        let activity = parse_activity(stdout);
+        assert_eq!(activity.get("eng-1"), Some(&250));
+        assert_eq!(activity.get("weird|name"), Some(&300));
+        assert_eq!(activity.len(), 2);
+    }
+}
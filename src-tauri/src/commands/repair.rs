@@ -0,0 +1,279 @@
+use super::pool::reset_repo_pool;
+use super::restore::move_worktree;
+use super::workspace::list_repo_dirs;
+use super::worktree::worktrees_base;
+use crate::refname::worktree_dir_name;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri_plugin_shell::ShellExt;
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairedWorktree {
+    pub branch: String,
+    pub old_path: String,
+    pub new_path: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UnresolvedWorktree {
+    pub branch: String,
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairReport {
+    pub repo_path: String,
+    pub fixed: Vec<RepairedWorktree>,
+    pub unresolved: Vec<UnresolvedWorktree>,
+}
+
+/// A linked worktree as recorded in the repo's `.git/worktrees/<id>/` admin dir.
+struct AdminEntry {
+    id: String,
+    branch: String,
+    /// Where git believes the worktree lives
+    recorded_path: PathBuf,
+}
+
+/// Target of a `.git` file (`gitdir: <path>`), if `dir` holds one.
+fn read_gitdir_file(dir: &Path) -> Option<PathBuf> {
+    let content = fs::read_to_string(dir.join(".git")).ok()?;
+    let target = PathBuf::from(content.trim().strip_prefix("gitdir:")?.trim());
+    // worktree.useRelativePaths writes paths relative to the worktree
+    Some(if target.is_absolute() {
+        target
+    } else {
+        dir.join(target)
+    })
+}
+
+/// Compare paths through symlinks (e.g. /var → /private/var on macOS).
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Whether `dir` is the checkout of admin entry `id` — wherever its repo now lives.
+fn is_checkout_of(dir: &Path, id: &str) -> bool {
+    read_gitdir_file(dir).is_some_and(|gitdir| {
+        gitdir.file_name().is_some_and(|n| n == id)
+            && gitdir
+                .parent()
+                .and_then(|p| p.file_name())
+                .is_some_and(|n| n == "worktrees")
+    })
+}
+
+async fn common_git_dir(app: &tauri::AppHandle, repo_path: &str) -> Result<PathBuf, String> {
+    let out = app
+        .shell()
+        .command("git")
+        .args(["-C", repo_path, "rev-parse", "--git-common-dir"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+    if !out.status.success() {
+        return Err(format!(
+            "Not a git repository: {repo_path}: {}",
+            String::from_utf8_lossy(&out.stderr)
+        ));
+    }
+    let dir = PathBuf::from(String::from_utf8_lossy(&out.stdout).trim());
+    Ok(if dir.is_absolute() {
+        dir
+    } else {
+        Path::new(repo_path).join(dir)
+    })
+}
+
+fn read_admin_entries(common_dir: &Path) -> Vec<AdminEntry> {
+    let Ok(entries) = fs::read_dir(common_dir.join("worktrees")) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let admin = entry.path();
+            let id = entry.file_name().to_string_lossy().to_string();
+            // gitdir holds "<worktree>/.git"
+            let gitdir = fs::read_to_string(admin.join("gitdir")).ok()?;
+            let recorded_path = PathBuf::from(gitdir.trim()).parent()?.to_path_buf();
+            let head = fs::read_to_string(admin.join("HEAD")).unwrap_or_default();
+            let branch = head
+                .trim()
+                .strip_prefix("ref: refs/heads/")
+                .unwrap_or_default()
+                .to_string();
+            Some(AdminEntry {
+                id,
+                branch,
+                recorded_path,
+            })
+        })
+        .collect()
+}
+
+/// Where a moved worktree may now be: the recorded path itself (repo moved, worktree
+/// did not), its expected place next to the repo, or a `<old-name>-worktrees` sibling
+/// left behind when the repo was renamed.
+fn candidate_paths(repo: &Path, entry: &AdminEntry) -> Vec<PathBuf> {
+    let mut candidates = vec![entry.recorded_path.clone()];
+    let dir_name = entry.recorded_path.file_name().map(|n| n.to_os_string());
+
+    if let Ok(base) = worktrees_base(repo) {
+        if !entry.branch.is_empty() {
+            candidates.push(base.join(worktree_dir_name(&entry.branch)));
+        }
+        if let Some(ref name) = dir_name {
+            candidates.push(base.join(name));
+        }
+    }
+
+    if let (Some(parent), Some(name)) = (repo.parent(), dir_name) {
+        if let Ok(siblings) = fs::read_dir(parent) {
+            for sibling in siblings.flatten() {
+                let path = sibling.path();
+                if path.is_dir() && path.to_string_lossy().ends_with("-worktrees") {
+                    candidates.push(path.join(&name));
+                }
+            }
+        }
+    }
+
+    candidates
+}
+
+/// Reconnect a repo with its linked worktrees after either side was moved or renamed.
+async fn repair_repo(app: &tauri::AppHandle, repo_path: &str) -> Result<RepairReport, String> {
+    let repo = Path::new(repo_path);
+    let common_dir = common_git_dir(app, repo_path).await?;
+    let mut report = RepairReport {
+        repo_path: repo_path.to_string(),
+        fixed: Vec::new(),
+        unresolved: Vec::new(),
+    };
+
+    let mut found: Vec<(AdminEntry, PathBuf)> = Vec::new();
+    for entry in read_admin_entries(&common_dir) {
+        // Healthy: the worktree is where git thinks, and points back at this repo
+        let admin_dir = common_dir.join("worktrees").join(&entry.id);
+        if read_gitdir_file(&entry.recorded_path)
+            .is_some_and(|gitdir| same_path(&gitdir, &admin_dir))
+        {
+            continue;
+        }
+
+        match candidate_paths(repo, &entry)
+            .into_iter()
+            .find(|c| is_checkout_of(c, &entry.id))
+        {
+            Some(path) => found.push((entry, path)),
+            None => report.unresolved.push(UnresolvedWorktree {
+                branch: entry.branch.clone(),
+                path: entry.recorded_path.to_string_lossy().to_string(),
+                reason: "Worktree directory not found; remove it to prune the stale entry"
+                    .to_string(),
+            }),
+        }
+    }
+
+    if found.is_empty() {
+        return Ok(report);
+    }
+
+    let mut args = vec![
+        "-C".to_string(),
+        repo_path.to_string(),
+        "worktree".to_string(),
+        "repair".to_string(),
+    ];
+    args.extend(found.iter().map(|(_, p)| p.to_string_lossy().to_string()));
+    let output = app
+        .shell()
+        .command("git")
+        .args(&args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+    // Trust the filesystem rather than git's output: a worktree is fixed once both
+    // links point at each other again
+    for (entry, path) in found {
+        let admin_dir = common_dir.join("worktrees").join(&entry.id);
+        let linked = read_gitdir_file(&path).is_some_and(|gitdir| same_path(&gitdir, &admin_dir))
+            && fs::read_to_string(admin_dir.join("gitdir"))
+                .is_ok_and(|g| same_path(Path::new(g.trim()), &path.join(".git")));
+        if linked {
+            report.fixed.push(RepairedWorktree {
+                branch: entry.branch,
+                old_path: entry.recorded_path.to_string_lossy().to_string(),
+                new_path: path.to_string_lossy().to_string(),
+            });
+        } else {
+            report.unresolved.push(UnresolvedWorktree {
+                branch: entry.branch,
+                path: path.to_string_lossy().to_string(),
+                reason: format!("git worktree repair failed: {}", stderr.trim()),
+            });
+        }
+    }
+
+    // Pooled worktrees are tracked by path; have the pool re-discover them
+    if !report.fixed.is_empty() {
+        reset_repo_pool(app, repo_path);
+    }
+
+    // Sessions, live and saved, know their task's worktree by path too
+    for fixed in &report.fixed {
+        if let Err(e) = move_worktree(app, repo_path, &fixed.old_path, &fixed.new_path).await {
+            log::warn!("Failed to update sessions of {}: {e}", fixed.new_path);
+        }
+    }
+
+    Ok(report)
+}
+
+/// Repair the worktrees of a single repository.
+#[tauri::command]
+pub async fn worktree_repair(
+    app: tauri::AppHandle,
+    repo_path: String,
+) -> Result<RepairReport, String> {
+    repair_repo(&app, &repo_path).await
+}
+
+/// Repair every repository of a workspace, e.g. after the workspace folder was moved.
+/// Only repos where something was fixed or left unresolved are reported.
+#[tauri::command]
+pub async fn workspace_repair(
+    app: tauri::AppHandle,
+    workspace_path: String,
+) -> Result<Vec<RepairReport>, String> {
+    let mut reports = Vec::new();
+    for repo in list_repo_dirs(Path::new(&workspace_path))? {
+        let repo_path = repo.to_string_lossy().to_string();
+        match repair_repo(&app, &repo_path).await {
+            Ok(report) if report.fixed.is_empty() && report.unresolved.is_empty() => {}
+            Ok(report) => reports.push(report),
+            Err(e) => reports.push(RepairReport {
+                repo_path,
+                fixed: Vec::new(),
+                unresolved: vec![UnresolvedWorktree {
+                    branch: String::new(),
+                    path: String::new(),
+                    reason: e,
+                }],
+            }),
+        }
+    }
+    Ok(reports)
+}
@@ -0,0 +1,346 @@
+use super::tmux::{directiv_panes, DirectivPane};
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri_plugin_shell::ShellExt;
+
+/// Window over which CPU usage is measured from `/proc`.
+#[cfg(target_os = "linux")]
+const CPU_SAMPLE: std::time::Duration = std::time::Duration::from_millis(500);
+/// `USER_HZ`, the unit of the CPU times in `/proc/<pid>/stat`.
+#[cfg(target_os = "linux")]
+const CLOCK_TICKS: f64 = 100.0;
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessUsage {
+    pub pid: u32,
+    pub name: String,
+    pub cpu_percent: f64,
+    pub rss_kb: u64,
+}
+
+/// Totals over a pane's process and everything below it.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PaneResources {
+    pub pane: String,
+    pub window_index: u32,
+    pub window_name: String,
+    /// `pane_current_command`
+    pub command: String,
+    /// Percent of one core, so over 100 when several cores are busy
+    pub cpu_percent: f64,
+    pub rss_kb: u64,
+    /// Processes below the pane's shell
+    pub processes: usize,
+    /// The descendant using the most CPU
+    pub busiest: Option<ProcessUsage>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionResources {
+    pub session: String,
+    pub issue: Option<String>,
+    /// Command running in the agent pane (the first pane without one)
+    pub foreground: Option<String>,
+    pub cpu_percent: f64,
+    pub rss_kb: u64,
+    pub processes: usize,
+    pub panes: Vec<PaneResources>,
+}
+
+struct Process {
+    ppid: u32,
+    name: String,
+    cpu_percent: f64,
+    /// `None` until read from `/proc/<pid>/status`
+    rss_kb: Option<u64>,
+}
+
+/// A snapshot of every process on the machine.
+pub(crate) struct ProcessTable {
+    processes: HashMap<u32, Process>,
+    children: HashMap<u32, Vec<u32>>,
+}
+
+impl ProcessTable {
+    fn new(processes: HashMap<u32, Process>) -> Self {
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (pid, process) in &processes {
+            children.entry(process.ppid).or_default().push(*pid);
+        }
+        Self {
+            processes,
+            children,
+        }
+    }
+
+    /// Every process below `pid`, not including it.
+    pub(crate) fn descendants(&self, pid: u32) -> Vec<u32> {
+        let mut found = Vec::new();
+        let mut stack = vec![pid];
+        while let Some(pid) = stack.pop() {
+            for child in self.children.get(&pid).into_iter().flatten() {
+                found.push(*child);
+                stack.push(*child);
+            }
+        }
+        found
+    }
+
+    pub(crate) fn name(&self, pid: u32) -> Option<&str> {
+        self.processes.get(&pid).map(|p| p.name.as_str())
+    }
+
+    fn usage(&self, pid: u32) -> Option<ProcessUsage> {
+        let process = self.processes.get(&pid)?;
+        Some(ProcessUsage {
+            pid,
+            name: process.name.clone(),
+            cpu_percent: process.cpu_percent,
+            rss_kb: process.rss_kb.unwrap_or_else(|| read_rss_kb(pid)),
+        })
+    }
+}
+
+/// Snapshot the process table. With `cpu`, usage is sampled over
+/// [`CPU_SAMPLE`] on Linux; elsewhere `ps` reports it averaged over each
+/// process's lifetime.
+pub(crate) async fn process_table(app: &tauri::AppHandle, cpu: bool) -> ProcessTable {
+    #[cfg(target_os = "linux")]
+    if let Some(table) = proc_table(cpu).await {
+        return table;
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = cpu;
+    ps_table(app).await
+}
+
+/// `(pid, ppid, comm, utime + stime)` from a `/proc/<pid>/stat` line.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_stat(line: &str) -> Option<(u32, u32, String, u64)> {
+    // comm is in parentheses and may itself contain spaces and parentheses
+    let open = line.find('(')?;
+    let close = line.rfind(')')?;
+    let pid = line[..open].trim().parse().ok()?;
+    let name = line.get(open + 1..close)?.to_string();
+    let fields: Vec<&str> = line[close + 1..].split_whitespace().collect();
+    let ppid = fields.get(1)?.parse().ok()?;
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some((pid, ppid, name, utime + stime))
+}
+
+#[cfg(target_os = "linux")]
+fn read_stats() -> HashMap<u32, (u32, String, u64)> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return HashMap::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| {
+            e.file_name()
+                .to_string_lossy()
+                .bytes()
+                .all(|b| b.is_ascii_digit())
+        })
+        .filter_map(|e| std::fs::read_to_string(e.path().join("stat")).ok())
+        .filter_map(|line| parse_stat(&line))
+        .map(|(pid, ppid, name, ticks)| (pid, (ppid, name, ticks)))
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+async fn proc_table(cpu: bool) -> Option<ProcessTable> {
+    let started = std::time::Instant::now();
+    let first = read_stats();
+    if first.is_empty() {
+        return None;
+    }
+    let (stats, seconds) = if cpu {
+        tokio::time::sleep(CPU_SAMPLE).await;
+        (read_stats(), started.elapsed().as_secs_f64())
+    } else {
+        (first.clone(), 0.0)
+    };
+
+    let processes = stats
+        .into_iter()
+        .map(|(pid, (ppid, name, ticks))| {
+            // A process started since the first sample used all its ticks in the window
+            let before = first.get(&pid).map_or(0, |(_, _, t)| *t);
+            let cpu_percent = if seconds > 0.0 {
+                ticks.saturating_sub(before) as f64 / CLOCK_TICKS / seconds * 100.0
+            } else {
+                0.0
+            };
+            let process = Process {
+                ppid,
+                name,
+                cpu_percent,
+                rss_kb: None,
+            };
+            (pid, process)
+        })
+        .collect();
+    Some(ProcessTable::new(processes))
+}
+
+/// Resident memory in KiB, 0 when the process is gone or not on Linux.
+fn read_rss_kb(pid: u32) -> u64 {
+    std::fs::read_to_string(format!("/proc/{pid}/status"))
+        .ok()
+        .and_then(|status| {
+            let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+            line.split_whitespace().nth(1)?.parse().ok()
+        })
+        .unwrap_or(0)
+}
+
+/// Parse `ps -A -o pid=,ppid=,pcpu=,rss=,comm=`. `ps` gives full paths on macOS.
+fn parse_ps(stdout: &str) -> HashMap<u32, Process> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let ppid = fields.next()?.parse().ok()?;
+            let cpu_percent = fields.next()?.parse().ok()?;
+            let rss_kb = fields.next()?.parse().ok()?;
+            let command = fields.collect::<Vec<_>>().join(" ");
+            let name = command.rsplit('/').next().unwrap_or_default().to_string();
+            let process = Process {
+                ppid,
+                name,
+                cpu_percent,
+                rss_kb: Some(rss_kb),
+            };
+            Some((pid, process))
+        })
+        .collect()
+}
+
+async fn ps_table(app: &tauri::AppHandle) -> ProcessTable {
+    let processes = match app
+        .shell()
+        .command("ps")
+        .args(["-A", "-o", "pid=,ppid=,pcpu=,rss=,comm="])
+        .output()
+        .await
+    {
+        Ok(output) => parse_ps(&String::from_utf8_lossy(&output.stdout)),
+        Err(e) => {
+            log::warn!("Failed to run ps: {e}");
+            HashMap::new()
+        }
+    };
+    ProcessTable::new(processes)
+}
+
+fn pane_resources(table: &ProcessTable, pane: &DirectivPane) -> PaneResources {
+    let descendants: Vec<ProcessUsage> = table
+        .descendants(pane.pid)
+        .into_iter()
+        .filter_map(|pid| table.usage(pid))
+        .collect();
+    let root = table.usage(pane.pid);
+    let all = || root.iter().chain(&descendants);
+    PaneResources {
+        pane: pane.id.clone(),
+        window_index: pane.window_index,
+        window_name: pane.window_name.clone(),
+        command: pane.command.clone(),
+        cpu_percent: all().map(|p| p.cpu_percent).sum(),
+        rss_kb: all().map(|p| p.rss_kb).sum(),
+        processes: descendants.len(),
+        busiest: descendants
+            .iter()
+            .max_by(|a, b| a.cpu_percent.total_cmp(&b.cpu_percent))
+            .cloned(),
+    }
+}
+
+/// CPU, memory and process count of every directiv session, summed over the
+/// process tree of each of its panes. Takes about half a second on Linux,
+/// where CPU usage is sampled.
+#[tauri::command]
+pub async fn tmux_session_resources(
+    app: tauri::AppHandle,
+) -> Result<Vec<SessionResources>, String> {
+    let panes = directiv_panes(&app).await?;
+    if panes.is_empty() {
+        return Ok(Vec::new());
+    }
+    let table = process_table(&app, true).await;
+
+    let mut sessions: Vec<SessionResources> = Vec::new();
+    for pane in &panes {
+        let usage = pane_resources(&table, pane);
+        let index = match sessions.iter().position(|s| s.session == pane.session) {
+            Some(index) => index,
+            None => {
+                sessions.push(SessionResources {
+                    session: pane.session.clone(),
+                    issue: pane.issue.clone(),
+                    foreground: None,
+                    cpu_percent: 0.0,
+                    rss_kb: 0,
+                    processes: 0,
+                    panes: Vec::new(),
+                });
+                sessions.len() - 1
+            }
+        };
+        let session = &mut sessions[index];
+        if pane.agent_pane.as_deref() == Some(pane.id.as_str()) || session.foreground.is_none() {
+            session.foreground = Some(pane.command.clone());
+        }
+        session.cpu_percent += usage.cpu_percent;
+        session.rss_kb += usage.rss_kb;
+        session.processes += usage.processes;
+        session.panes.push(usage);
+    }
+    Ok(sessions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stat_lines_with_odd_command_names() {
+        let line = "4242 (npm (test) x) S 4100 4242 4100 0 -1 4194304 1 0 0 0 \
+                    150 25 0 0 20 0 1 0 100 1000 200 18446744073709551615";
+        assert_eq!(
+            parse_stat(line),
+            Some((4242, 4100, "npm (test) x".to_string(), 175))
+        );
+        assert_eq!(parse_stat("garbage"), None);
+    }
+
+    #[test]
+    fn tree_totals_cover_every_descendant() {
+        let ps = "  10     1  0.0  3000 /bin/zsh\n\
+                  \x20 11    10 90.0 50000 node\n\
+                  \x20 12    11 10.0 20000 /usr/bin/jest worker\n\
+                  \x20 13     1 50.0  9999 other\n";
+        let table = ProcessTable::new(parse_ps(ps));
+        let pane = DirectivPane {
+            session: "eng-1".to_string(),
+            issue: None,
+            agent_pane: None,
+            id: "%1".to_string(),
+            window_index: 0,
+            window_name: "main".to_string(),
+            pid: 10,
+            command: "node".to_string(),
+        };
+        let usage = pane_resources(&table, &pane);
+        assert_eq!(usage.processes, 2);
+        assert_eq!(usage.rss_kb, 73000);
+        assert_eq!(usage.cpu_percent, 100.0);
+        assert_eq!(usage.busiest.map(|p| p.name), Some("node".to_string()));
+        assert_eq!(table.name(12), Some("jest worker"));
+    }
+}
@@ -0,0 +1,588 @@
+use super::agent_hooks::AgentHooks;
+use super::config::directiv_home;
+use super::gc::now_secs;
+use super::logs::shell_quote;
+use super::readiness::tmux_wait_for_ready;
+use super::tmux::{
+    decode_option, directiv_sessions, retag_session, start_agent, tmux_command,
+    tmux_create_session, tmux_list_sessions, OPT_AGENT_COMMAND,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::Manager;
+
+/// How often the saved sessions are brought up to date.
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// `start_time` of the last tmux server seen running since the app started, 0
+/// before any.
+static SEEN_SERVER: AtomicU64 = AtomicU64::new(0);
+
+/// A task session as saved in `~/.directiv/sessions.json`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedSession {
+    pub name: String,
+    pub issue: Option<String>,
+    pub repo: Option<String>,
+    pub worktree: Option<String>,
+    pub agent: Option<String>,
+    /// What the agent was started with, see `tmux_start_agent`
+    pub agent_command: Option<String>,
+    /// The agent's conversation, as reported by its hooks
+    pub agent_session_id: Option<String>,
+    pub windows: Vec<SavedWindow>,
+    /// Unix seconds the session was last seen running
+    pub saved_at: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedWindow {
+    pub index: u32,
+    pub name: String,
+    /// `window_layout`, for `select-layout`
+    pub layout: String,
+    /// Working directory of each pane
+    pub panes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoredSession {
+    pub name: String,
+    pub restored: bool,
+    /// The agent continues its previous conversation
+    pub resumed: bool,
+    pub message: String,
+}
+
+fn sessions_path() -> Result<PathBuf, String> {
+    Ok(directiv_home()?.join("sessions.json"))
+}
+
+fn read_saved() -> Result<Vec<SavedSession>, String> {
+    let path = sessions_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let raw =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    serde_json::from_str(&raw).map_err(|e| format!("Failed to parse {}: {e}", path.display()))
+}
+
+fn write_saved(sessions: &[SavedSession]) -> Result<(), String> {
+    let path = sessions_path()?;
+    let json = serde_json::to_string_pretty(sessions).map_err(|e| e.to_string())?;
+    // Written aside and renamed, so a crash never leaves half a file
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| format!("Failed to write {}: {e}", tmp.display()))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// `path` with its `old` prefix replaced by `new`, `None` when it isn't inside `old`.
+fn moved_path(path: &str, old: &str, new: &str) -> Option<String> {
+    let rest = path.strip_prefix(old)?;
+    (rest.is_empty() || rest.starts_with('/')).then(|| format!("{new}{rest}"))
+}
+
+/// Follow a worktree from `old` to `new` in the running sessions' tags and the
+/// saved sessions, so both keep mapping their task to it.
+pub(crate) async fn move_worktree(
+    app: &tauri::AppHandle,
+    repo: &str,
+    old: &str,
+    new: &str,
+) -> Result<(), String> {
+    for session in directiv_sessions(app).await.unwrap_or_default() {
+        let Some(worktree) = session.worktree.as_deref() else {
+            continue;
+        };
+        if let Some(moved) = moved_path(worktree, old, new) {
+            retag_session(app, &session.name, &moved, repo).await?;
+        }
+    }
+
+    let mut saved = read_saved()?;
+    let mut changed = false;
+    for session in &mut saved {
+        let Some(worktree) = session.worktree.as_deref() else {
+            continue;
+        };
+        let Some(moved) = moved_path(worktree, old, new) else {
+            continue;
+        };
+        session.worktree = Some(moved);
+        session.repo = Some(repo.to_string());
+        for pane in session.windows.iter_mut().flat_map(|w| w.panes.iter_mut()) {
+            if let Some(moved) = moved_path(pane, old, new) {
+                *pane = moved;
+            }
+        }
+        changed = true;
+    }
+    if changed {
+        write_saved(&saved)?;
+    }
+    Ok(())
+}
+
+/// Forget a saved session that was ended on purpose, so it is never restored.
+pub(crate) fn forget_saved(name: &str) -> Result<(), String> {
+    let mut saved = read_saved()?;
+    let count = saved.len();
+    saved.retain(|s| s.name != name);
+    if saved.len() == count {
+        return Ok(());
+    }
+    write_saved(&saved)
+}
+
+/// Whether a saved session that isn't running died with its tmux server (e.g. at
+/// a reboot) rather than being ended on purpose. `running` is the start of the
+/// server running now, `seen` the start of the last one seen while the app ran:
+/// a session saved while either was up ended without its server going down with
+/// it, or the server stopped because its last session was killed.
+fn died_with_server(saved_at: u64, running: Option<u64>, seen: Option<u64>) -> bool {
+    match running.or(seen) {
+        Some(started) => started > saved_at,
+        // No server since the app started, e.g. right after a reboot
+        None => true,
+    }
+}
+
+/// What tmux knows about its sessions beyond `tmux_list_sessions`.
+#[derive(Debug, Default, PartialEq)]
+struct Snapshot {
+    /// `start_time` of the server, `None` when none is running
+    server_started: Option<u64>,
+    agent_commands: HashMap<String, String>,
+    windows: HashMap<String, Vec<SavedWindow>>,
+}
+
+/// Parse the `S|`, `W|` and `P|` lines listed by [`snapshot`].
+fn parse_snapshot(stdout: &str) -> Snapshot {
+    let mut snapshot = Snapshot::default();
+    for line in stdout.lines() {
+        if let Some(rest) = line.strip_prefix("S|") {
+            let mut parts = rest.splitn(3, '|');
+            let (Some(started), Some(session)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            snapshot.server_started = started.parse().ok();
+            if let Some(command) = parts.next().and_then(decode_option) {
+                snapshot.agent_commands.insert(session.to_string(), command);
+            }
+        } else if let Some(rest) = line.strip_prefix("W|") {
+            let mut parts = rest.splitn(4, '|');
+            let (Some(session), Some(index), Some(layout), Some(name)) =
+                (parts.next(), parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            let Ok(index) = index.parse() else {
+                continue;
+            };
+            snapshot
+                .windows
+                .entry(session.to_string())
+                .or_default()
+                .push(SavedWindow {
+                    index,
+                    name: name.to_string(),
+                    layout: layout.to_string(),
+                    panes: Vec::new(),
+                });
+        } else if let Some(rest) = line.strip_prefix("P|") {
+            let mut parts = rest.splitn(3, '|');
+            let (Some(session), Some(index), Some(path)) =
+                (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            let window = snapshot
+                .windows
+                .get_mut(session)
+                .and_then(|windows| windows.iter_mut().find(|w| index == w.index.to_string()));
+            if let Some(window) = window {
+                window.panes.push(path.to_string());
+            }
+        }
+    }
+    snapshot
+}
+
+async fn snapshot(app: &tauri::AppHandle) -> Snapshot {
+    let output = tmux_command(app)
+        .args([
+            "list-sessions",
+            "-F",
+            &format!("S|#{{start_time}}|#{{session_name}}|#{{{OPT_AGENT_COMMAND}}}"),
+            ";",
+            "list-windows",
+            "-a",
+            "-F",
+            "W|#{session_name}|#{window_index}|#{window_layout}|#{window_name}",
+            ";",
+            "list-panes",
+            "-a",
+            "-F",
+            "P|#{session_name}|#{window_index}|#{pane_current_path}",
+        ])
+        .output()
+        .await;
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_snapshot(&String::from_utf8_lossy(&output.stdout))
+        }
+        // No server running
+        _ => Snapshot::default(),
+    }
+}
+
+/// Save every running directiv session, keeping saved ones that are gone because
+/// the tmux server restarted (e.g. after a reboot). Sessions that ended while
+/// the server kept running were closed on purpose and are dropped.
+async fn save_sessions(app: &tauri::AppHandle) -> Result<Vec<SavedSession>, String> {
+    let live = directiv_sessions(app).await?;
+    let mut snapshot = snapshot(app).await;
+    let conversations: HashMap<String, Option<String>> = app
+        .state::<AgentHooks>()
+        .states()
+        .into_iter()
+        .map(|s| (s.worktree, s.session_id))
+        .collect();
+    let now = now_secs();
+
+    let (previous, mut saved): (Vec<SavedSession>, Vec<SavedSession>) = read_saved()
+        .unwrap_or_else(|e| {
+            log::warn!("Discarding saved sessions: {e}");
+            Vec::new()
+        })
+        .into_iter()
+        .partition(|s| live.iter().any(|l| l.name == s.name));
+    // Hook states start empty with the app; keep the conversation saved before
+    let previous_ids: HashMap<String, String> = previous
+        .into_iter()
+        .filter_map(|s| Some((s.name, s.agent_session_id?)))
+        .collect();
+    let seen = Some(SEEN_SERVER.load(Ordering::Relaxed)).filter(|&t| t > 0);
+    saved.retain(|s| {
+        died_with_server(s.saved_at, snapshot.server_started, seen)
+            && s.worktree.as_ref().map_or(true, |w| Path::new(w).exists())
+    });
+    if let Some(started) = snapshot.server_started {
+        SEEN_SERVER.store(started, Ordering::Relaxed);
+    }
+
+    for session in live {
+        let agent_session_id = session
+            .worktree
+            .as_ref()
+            .and_then(|w| conversations.get(w).cloned().flatten())
+            .or_else(|| previous_ids.get(&session.name).cloned());
+        saved.push(SavedSession {
+            agent_command: snapshot.agent_commands.remove(&session.name),
+            windows: snapshot.windows.remove(&session.name).unwrap_or_default(),
+            name: session.name,
+            issue: session.issue,
+            repo: session.repo,
+            worktree: session.worktree,
+            agent: session.agent,
+            agent_session_id,
+            saved_at: now,
+        });
+    }
+    saved.sort_by(|a, b| a.name.cmp(&b.name));
+    write_saved(&saved)?;
+    Ok(saved)
+}
+
+/// Words of a shell command as written, quotes included.
+fn shell_words(command: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in command.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if quote == Some(c) {
+            quote = None;
+        } else if quote.is_none() && (c == '\'' || c == '"') {
+            quote = Some(c);
+        } else if c == '\\' && quote != Some('\'') {
+            escaped = true;
+        } else if quote.is_none() && c.is_whitespace() {
+            if let Some(s) = start.take() {
+                words.push(&command[s..i]);
+            }
+            continue;
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(s) = start {
+        words.push(&command[s..]);
+    }
+    words
+}
+
+/// The command restarting the agent of a restored session. Claude Code resumes
+/// its conversation (`--resume <id>`, or `--continue` for the latest one in the
+/// worktree) with the saved command's flags, minus the initial prompt. Agents
+/// without resume support run their saved command again.
+fn resume_command(agent: Option<&str>, command: &str, session_id: Option<&str>) -> (String, bool) {
+    let words = shell_words(command);
+    let Some((program, args)) = words.split_first() else {
+        return (command.to_string(), false);
+    };
+    let is_claude = agent == Some("claude") || program.rsplit('/').next() == Some("claude");
+    if !is_claude {
+        return (command.to_string(), false);
+    }
+
+    let mut resumed = vec![program.to_string()];
+    match session_id {
+        Some(id) => resumed.extend(["--resume".to_string(), shell_quote(id)]),
+        None => resumed.push("--continue".to_string()),
+    }
+    let flags = match args.first() {
+        Some(prompt) if !prompt.starts_with('-') => &args[1..],
+        _ => args,
+    };
+    resumed.extend(flags.iter().map(|w| w.to_string()));
+    (resumed.join(" "), true)
+}
+
+async fn restore_windows(app: &tauri::AppHandle, name: &str, windows: &[SavedWindow]) {
+    let existing = snapshot(app).await.windows.remove(name).unwrap_or_default();
+    for window in windows {
+        let target = format!("={name}:{}", window.index);
+        let mut args: Vec<String> = Vec::new();
+        match existing.iter().find(|w| w.index == window.index) {
+            // A window of the repo's layout; only its pane sizes are restored
+            Some(current) if current.panes.len() == window.panes.len() => {}
+            Some(_) => continue,
+            None => {
+                let Some((first, rest)) = window.panes.split_first() else {
+                    continue;
+                };
+                args.extend(
+                    [
+                        "new-window",
+                        "-d",
+                        "-t",
+                        &target,
+                        "-n",
+                        &window.name,
+                        "-c",
+                        first,
+                    ]
+                    .map(str::to_string),
+                );
+                for dir in rest {
+                    args.extend(
+                        [";", "split-window", "-d", "-t", &target, "-c", dir].map(str::to_string),
+                    );
+                }
+                args.push(";".to_string());
+            }
+        }
+        args.extend(["select-layout", "-t", &target, &window.layout].map(str::to_string));
+
+        match tmux_command(app).args(&args).output().await {
+            Ok(output) if output.status.success() => {}
+            Ok(output) => log::warn!(
+                "Failed to restore window {} of {name}: {}",
+                window.index,
+                String::from_utf8_lossy(&output.stderr)
+            ),
+            Err(e) => log::warn!("Failed to restore window {} of {name}: {e}", window.index),
+        }
+    }
+}
+
+async fn restore_session(app: &tauri::AppHandle, saved: &SavedSession) -> RestoredSession {
+    let result = |restored: bool, resumed: bool, message: String| RestoredSession {
+        name: saved.name.clone(),
+        restored,
+        resumed,
+        message,
+    };
+
+    let session = match tmux_create_session(
+        app.clone(),
+        saved.name.clone(),
+        saved.worktree.clone(),
+        saved.issue.clone(),
+        saved.repo.clone(),
+        saved.agent.clone(),
+        None,
+        None,
+    )
+    .await
+    {
+        // The task was started again since, in a session of its own
+        Ok(session) if session.name != saved.name => {
+            return result(false, false, format!("Already running as {}", session.name))
+        }
+        Ok(session) => session,
+        Err(e) => return result(false, false, e),
+    };
+
+    match tmux_wait_for_ready(app.clone(), session.name.clone(), None).await {
+        Ok(report) if report.ready => {}
+        Ok(report) => {
+            let reason = report.diagnostic.map(|d| d.reason).unwrap_or_default();
+            return result(true, false, format!("The shell did not start: {reason}"));
+        }
+        Err(e) => return result(true, false, e),
+    }
+    restore_windows(app, &session.name, &saved.windows).await;
+
+    let Some(command) = &saved.agent_command else {
+        return result(true, false, "Restored without an agent".to_string());
+    };
+    let (command, resumed) = resume_command(
+        saved.agent.as_deref(),
+        command,
+        saved.agent_session_id.as_deref(),
+    );
+    match start_agent(app, &session.name, &command, session.agent_pane).await {
+        Ok(()) if resumed => result(true, true, "Resumed the agent's conversation".to_string()),
+        Ok(()) => result(true, false, "Started the agent again".to_string()),
+        Err(e) => result(true, false, format!("Failed to start the agent: {e}")),
+    }
+}
+
+/// Save the running directiv sessions to `~/.directiv/sessions.json` now rather
+/// than at the next periodic save.
+#[tauri::command]
+pub async fn tmux_save_sessions(app: tauri::AppHandle) -> Result<Vec<SavedSession>, String> {
+    save_sessions(&app).await
+}
+
+/// Recreate the saved sessions that are no longer running, e.g. after a reboot.
+/// Sessions whose worktree is gone are forgotten instead.
+#[tauri::command]
+pub async fn tmux_restore_sessions(app: tauri::AppHandle) -> Result<Vec<RestoredSession>, String> {
+    let live: HashSet<String> = tmux_list_sessions(app.clone())
+        .await?
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+    let saved = read_saved()?;
+
+    let mut results = Vec::new();
+    let mut kept = Vec::new();
+    for session in saved {
+        if live.contains(&session.name) {
+            kept.push(session);
+            continue;
+        }
+        if session
+            .worktree
+            .as_ref()
+            .is_some_and(|w| !Path::new(w).exists())
+        {
+            results.push(RestoredSession {
+                name: session.name,
+                restored: false,
+                resumed: false,
+                message: "The worktree is gone".to_string(),
+            });
+            continue;
+        }
+        results.push(restore_session(&app, &session).await);
+        kept.push(session);
+    }
+    write_saved(&kept)?;
+    Ok(results)
+}
+
+/// Keep `~/.directiv/sessions.json` up to date in the background.
+pub fn spawn_session_saver(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SAVE_INTERVAL).await;
+            if let Err(e) = save_sessions(&app).await {
+                log::warn!("Failed to save sessions: {e}");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_groups_windows_and_panes_by_session() {
+        let stdout = "S|1700000000|eng-1|claude 'go' %7C x\n\
+                      S|1700000000|eng-2|\n\
+                      W|eng-1|0|b25f,80x24,0,0,1|main\n\
+                      W|eng-1|1|a1b2,80x24,0,0,2|tests|watch\n\
+                      P|eng-1|0|/wt/eng-1\n\
+                      P|eng-1|1|/wt/eng-1\n\
+                      P|eng-1|1|/wt/eng-1/web\n";
+        let snapshot = parse_snapshot(stdout);
+        assert_eq!(snapshot.server_started, Some(1_700_000_000));
+        assert_eq!(snapshot.agent_commands.len(), 1);
+        assert_eq!(snapshot.agent_commands["eng-1"], "claude 'go' | x");
+        let windows = &snapshot.windows["eng-1"];
+        assert_eq!(windows[1].name, "tests|watch");
+        assert_eq!(windows[1].panes, vec!["/wt/eng-1", "/wt/eng-1/web"]);
+    }
+
+    #[test]
+    fn claude_resumes_without_its_initial_prompt() {
+        let command = r"claude '/directiv:linear-issue ENG-1 it'\''s' --plugin-dir '/a b'";
+        assert_eq!(
+            resume_command(Some("claude"), command, Some("abc-123")),
+            (
+                "claude --resume 'abc-123' --plugin-dir '/a b'".to_string(),
+                true
+            )
+        );
+        assert_eq!(
+            resume_command(None, "claude --plugin-dir /p", None),
+            ("claude --continue --plugin-dir /p".to_string(), true)
+        );
+        assert_eq!(
+            resume_command(Some("aider"), "aider --yes", Some("x")),
+            ("aider --yes".to_string(), false)
+        );
+    }
+
+    #[test]
+    fn sessions_saved_on_a_server_seen_running_are_not_kept() {
+        // Restarted server: only sessions from before it died with the old one
+        assert!(died_with_server(100, Some(200), Some(200)));
+        assert!(!died_with_server(300, Some(200), Some(200)));
+        // Killing the last session stopped the server the app saw running
+        assert!(!died_with_server(300, None, Some(200)));
+        assert!(died_with_server(100, None, Some(200)));
+        // No server since launch, e.g. after a reboot
+        assert!(died_with_server(300, None, None));
+    }
+
+    #[test]
+    fn moved_paths_keep_their_place_inside_the_worktree() {
+        assert_eq!(
+            moved_path("/a/wt/eng-1", "/a/wt/eng-1", "/b/wt/eng-1").as_deref(),
+            Some("/b/wt/eng-1")
+        );
+        assert_eq!(
+            moved_path("/a/wt/eng-1/web", "/a/wt/eng-1", "/b/wt/eng-1").as_deref(),
+            Some("/b/wt/eng-1/web")
+        );
+        assert_eq!(
+            moved_path("/a/wt/eng-10", "/a/wt/eng-1", "/b/wt/eng-1"),
+            None
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use super::config::directiv_home;
+use super::tmux::{capture, directiv_panes, CaptureOptions};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::ipc::Channel;
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchOptions {
+    /// Treat the query as a regular expression rather than literal text
+    pub regex: bool,
+    pub case_sensitive: bool,
+    /// Lines of context before and after each match
+    pub context: usize,
+    pub sessions: bool,
+    pub logs: bool,
+    /// Stop once this many matches were found
+    pub max_results: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            regex: false,
+            case_sensitive: false,
+            context: 2,
+            sessions: true,
+            logs: true,
+            max_results: 500,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LineMatch {
+    /// 1-based, from the start of the scrollback or log
+    pub line_number: usize,
+    pub line: String,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase", tag = "source")]
+pub enum SearchHit {
+    /// Scrollback of a live pane
+    #[serde(rename_all = "camelCase")]
+    Session {
+        session: String,
+        issue: Option<String>,
+        window_index: u32,
+        window_name: String,
+        pane: String,
+        #[serde(flatten)]
+        found: LineMatch,
+    },
+    /// A persisted log under `~/.directiv/logs/<repo>/<task>`
+    #[serde(rename_all = "camelCase")]
+    Log {
+        repo: String,
+        task: String,
+        log: String,
+        #[serde(flatten)]
+        found: LineMatch,
+    },
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase", tag = "event", content = "data")]
+pub enum SearchEvent {
+    Hit(SearchHit),
+    Done {
+        hits: usize,
+        /// `maxResults` was reached before everything was searched
+        truncated: bool,
+    },
+}
+
+/// Matching lines of `text`, with up to `context` lines around each.
+fn find_matches(text: &str, pattern: &Regex, context: usize) -> Vec<LineMatch> {
+    let mut lines: Vec<&str> = text.lines().collect();
+    // Captures end with the blank rows below the cursor
+    while lines.last().is_some_and(|l| l.trim().is_empty()) {
+        lines.pop();
+    }
+
+    let owned = |slice: &[&str]| slice.iter().map(|l| l.to_string()).collect();
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| pattern.is_match(line))
+        .map(|(i, line)| LineMatch {
+            line_number: i + 1,
+            line: line.to_string(),
+            before: owned(&lines[i.saturating_sub(context)..i]),
+            after: owned(&lines[i + 1..(i + 1 + context).min(lines.len())]),
+        })
+        .collect()
+}
+
+fn build_pattern(query: &str, options: &SearchOptions) -> Result<Regex, String> {
+    if query.is_empty() {
+        return Err("Search query is empty".to_string());
+    }
+    let source = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    RegexBuilder::new(&source)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid pattern: {e}"))
+}
+
+/// `(repo, task, path)` of every persisted log, grouped by task.
+fn log_files() -> Result<Vec<(String, String, PathBuf)>, String> {
+    let dir_entries = |dir: &Path| -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+            .map(|entries| entries.flatten().map(|e| e.path()).collect())
+            .unwrap_or_default();
+        paths.sort();
+        paths
+    };
+    let name = |path: &Path| {
+        path.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+
+    let mut files = Vec::new();
+    for repo in dir_entries(&directiv_home()?.join("logs")) {
+        for task in dir_entries(&repo).into_iter().filter(|p| p.is_dir()) {
+            for log in dir_entries(&task) {
+                if log.extension().is_some_and(|ext| ext == "log") {
+                    files.push((name(&repo), name(&task), log));
+                }
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Search the scrollback of every directiv session, then the persisted session
+/// logs, streaming each match to `on_event` as it is found. Ends with a
+/// [`SearchEvent::Done`].
+#[tauri::command]
+pub async fn session_search(
+    app: tauri::AppHandle,
+    query: String,
+    options: Option<SearchOptions>,
+    on_event: Channel<SearchEvent>,
+) -> Result<(), String> {
+    let options = options.unwrap_or_default();
+    let pattern = build_pattern(&query, &options)?;
+    let max = options.max_results.max(1);
+    let mut hits = 0;
+
+    // Returns false once the search should stop
+    let mut send = |hit: SearchHit| -> Result<bool, String> {
+        on_event
+            .send(SearchEvent::Hit(hit))
+            .map_err(|e| format!("Failed to send search result: {e}"))?;
+        hits += 1;
+        Ok(hits < max)
+    };
+
+    let mut truncated = false;
+    if options.sessions {
+        let history = CaptureOptions {
+            start: Some("-".to_string()),
+            join: true,
+            ..Default::default()
+        };
+        'panes: for pane in directiv_panes(&app).await? {
+            // The pane may have closed since it was listed
+            let text = match capture(&app, &pane.session, Some(&pane.id), &history).await {
+                Ok(text) => text,
+                Err(e) => {
+                    log::warn!("Skipping pane {} of {}: {e}", pane.id, pane.session);
+                    continue;
+                }
+            };
+            for found in find_matches(&text, &pattern, options.context) {
+                let more = send(SearchHit::Session {
+                    session: pane.session.clone(),
+                    issue: pane.issue.clone(),
+                    window_index: pane.window_index,
+                    window_name: pane.window_name.clone(),
+                    pane: pane.id.clone(),
+                    found,
+                })?;
+                if !more {
+                    truncated = true;
+                    break 'panes;
+                }
+            }
+        }
+    }
+
+    if options.logs && !truncated {
+        'logs: for (repo, task, path) in log_files()? {
+            let Ok(bytes) = fs::read(&path) else {
+                continue;
+            };
+            let text = String::from_utf8_lossy(&bytes);
+            for found in find_matches(&text, &pattern, options.context) {
+                let more = send(SearchHit::Log {
+                    repo: repo.clone(),
+                    task: task.clone(),
+                    log: path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    found,
+                })?;
+                if !more {
+                    truncated = true;
+                    break 'logs;
+                }
+            }
+        }
+    }
+
+    on_event
+        .send(SearchEvent::Done { hits, truncated })
+        .map_err(|e| format!("Failed to send search result: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_carry_clamped_context() {
+        let pattern = build_pattern("error", &SearchOptions::default()).unwrap();
+        let found = find_matches("a\nERROR one\nb\nc\nerror two\n\n\n", &pattern, 2);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].line_number, 2);
+        assert_eq!(found[0].before, vec!["a"]);
+        assert_eq!(found[0].after, vec!["b", "c"]);
+        assert_eq!(found[1].before, vec!["b", "c"]);
+        assert!(found[1].after.is_empty());
+    }
+
+    #[test]
+    fn literal_queries_are_escaped() {
+        let options = SearchOptions {
+            case_sensitive: true,
+            ..Default::default()
+        };
+        let pattern = build_pattern("a.b(", &options).unwrap();
+        assert!(pattern.is_match("x a.b( y"));
+        assert!(!pattern.is_match("axb("));
+
+        let options = SearchOptions {
+            regex: true,
+            ..options
+        };
+        assert!(build_pattern("a(", &options).is_err());
+        assert!(build_pattern("^fail(ed|ure)", &options)
+            .unwrap()
+            .is_match("failure"));
+    }
+}
@@ -0,0 +1,404 @@
+use super::worktree::{
+    detect_default_branch, git_fetch_prune, landed_evidence, parse_worktree_porcelain,
+    LandedEvidence,
+};
+use crate::refname::validate_branch_name;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use tauri_plugin_shell::process::Output;
+use tauri_plugin_shell::ShellExt;
+
+// Stack metadata lives in the repo's git config, next to the branch it describes:
+//   branch.<name>.directiv-parent      → parent task branch
+//   branch.<name>.directiv-parent-base → parent commit the branch was last based on
+// The base commit is what lets a restack drop the parent's commits once they land
+// on the default branch through a squash merge.
+const PARENT_KEY: &str = "directiv-parent";
+const PARENT_BASE_KEY: &str = "directiv-parent-base";
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestackStatus {
+    /// Rebased onto the new tip of its parent branch
+    Rebased,
+    /// Parent landed: rebased onto the default branch and detached from the stack
+    Retargeted,
+    /// Not attempted (no worktree, dirty, or parent was not restacked)
+    Skipped,
+    /// Rebase failed and was aborted
+    Failed,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RestackResult {
+    pub branch: String,
+    pub onto: String,
+    pub status: RestackStatus,
+    pub message: Option<String>,
+}
+
+async fn run_git(app: &tauri::AppHandle, args: &[&str]) -> Result<Output, String> {
+    app.shell()
+        .command("git")
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {e}"))
+}
+
+fn config_key(branch: &str, key: &str) -> String {
+    format!("branch.{branch}.{key}")
+}
+
+/// Map of stacked branch → parent branch, read from the repo's git config.
+pub(crate) async fn read_stack_parents(
+    app: &tauri::AppHandle,
+    repo_path: &str,
+) -> HashMap<String, String> {
+    let pattern = format!(r"^branch\..*\.{PARENT_KEY}$");
+    let Ok(out) = run_git(app, &["-C", repo_path, "config", "--get-regexp", &pattern]).await else {
+        return HashMap::new();
+    };
+    // Exit code 1 simply means no branch is stacked
+    if !out.status.success() {
+        return HashMap::new();
+    }
+
+    let suffix = format!(".{PARENT_KEY}");
+    String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .filter_map(|line| {
+            // "branch.feat/ACQ-2.directiv-parent ACQ-1"
+            let (key, parent) = line.split_once(' ')?;
+            let branch = key.strip_prefix("branch.")?.strip_suffix(&suffix)?;
+            Some((branch.to_string(), parent.trim().to_string()))
+        })
+        .collect()
+}
+
+async fn rev_parse(app: &tauri::AppHandle, repo_path: &str, rev: &str) -> Option<String> {
+    let out = run_git(
+        app,
+        &["-C", repo_path, "rev-parse", "--verify", "--quiet", rev],
+    )
+    .await
+    .ok()?;
+    out.status
+        .success()
+        .then(|| String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+async fn set_config(
+    app: &tauri::AppHandle,
+    repo_path: &str,
+    key: &str,
+    value: &str,
+) -> Result<(), String> {
+    let out = run_git(app, &["-C", repo_path, "config", key, value]).await?;
+    if !out.status.success() {
+        return Err(format!(
+            "git config {key} failed: {}",
+            String::from_utf8_lossy(&out.stderr)
+        ));
+    }
+    Ok(())
+}
+
+/// Record `parent` as the stack parent of `branch`, based on the parent's current tip.
+pub(crate) async fn record_stack_parent(
+    app: &tauri::AppHandle,
+    repo_path: &str,
+    branch: &str,
+    parent: &str,
+) -> Result<(), String> {
+    let base = rev_parse(app, repo_path, parent)
+        .await
+        .ok_or_else(|| format!("Parent branch not found: {parent}"))?;
+    set_config(app, repo_path, &config_key(branch, PARENT_KEY), parent).await?;
+    set_config(app, repo_path, &config_key(branch, PARENT_BASE_KEY), &base).await
+}
+
+async fn clear_stack_parent(app: &tauri::AppHandle, repo_path: &str, branch: &str) {
+    for key in [PARENT_KEY, PARENT_BASE_KEY] {
+        let _ = run_git(
+            app,
+            &[
+                "-C",
+                repo_path,
+                "config",
+                "--unset",
+                &config_key(branch, key),
+            ],
+        )
+        .await;
+    }
+}
+
+/// Whether the parent's work is on the default branch, so its children can be retargeted.
+///
+/// A parent deleted locally (e.g. worktree removed after merge) is judged by the commit
+/// `child` was last based on. Its upstream config went with the branch, so a remote
+/// branch that was seen holding that commit, and is now gone, stands in for it.
+async fn parent_landed(
+    app: &tauri::AppHandle,
+    repo_path: &str,
+    child: &str,
+    parent: &str,
+    default_branch: &str,
+) -> Result<bool, String> {
+    let deleted = rev_parse(app, repo_path, &format!("refs/heads/{parent}"))
+        .await
+        .is_none();
+    if !deleted {
+        let evidence = landed_evidence(app, repo_path, parent, parent, default_branch).await?;
+        return Ok(evidence.landed());
+    }
+
+    let Some(base) = stored_base(app, repo_path, child).await? else {
+        return Ok(false);
+    };
+    let evidence = landed_evidence(app, repo_path, parent, &base, default_branch).await?;
+    Ok(deleted_parent_landed(evidence))
+}
+
+fn deleted_parent_landed(evidence: LandedEvidence) -> bool {
+    LandedEvidence {
+        tracks_own_remote: true,
+        ..evidence
+    }
+    .landed()
+}
+
+/// The parent commit `branch` was last based on, from its stack config.
+async fn stored_base(
+    app: &tauri::AppHandle,
+    repo_path: &str,
+    branch: &str,
+) -> Result<Option<String>, String> {
+    let out = run_git(
+        app,
+        &[
+            "-C",
+            repo_path,
+            "config",
+            "--get",
+            &config_key(branch, PARENT_BASE_KEY),
+        ],
+    )
+    .await?;
+    Ok(out
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&out.stdout).trim().to_string()))
+}
+
+/// Rebase every branch of the stack containing `branch`, parents before children.
+///
+/// Each stacked branch is rebased (in its own worktree) from the parent commit it was
+/// last based on onto the parent's current tip. When a parent has landed on the default
+/// branch, its children are rebased onto the default branch instead and leave the stack.
+/// A failed rebase is aborted and its descendants are skipped.
+#[tauri::command]
+pub async fn worktree_restack(
+    app: tauri::AppHandle,
+    repo_path: String,
+    branch: String,
+) -> Result<Vec<RestackResult>, String> {
+    validate_branch_name(&branch)?;
+
+    // Fresh remote state is needed to notice merged parents
+    if let Err(e) = git_fetch_prune(app.clone(), repo_path.clone()).await {
+        log::warn!("Restack fetch failed (continuing): {e}");
+    }
+
+    let parents = read_stack_parents(&app, &repo_path).await;
+
+    // Walk up to the bottom of the stack (guarding against cycles in hand-edited config)
+    let mut root = branch.clone();
+    let mut seen = HashSet::from([root.clone()]);
+    while let Some(parent) = parents.get(&root) {
+        if !seen.insert(parent.clone()) {
+            return Err(format!("Stack of {branch} contains a cycle at {parent}"));
+        }
+        root = parent.clone();
+    }
+
+    // Breadth-first order guarantees a parent is restacked before its children
+    let mut children: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (child, parent) in &parents {
+        children
+            .entry(parent.as_str())
+            .or_default()
+            .push(child.as_str());
+    }
+    children.values_mut().for_each(|kids| kids.sort_unstable());
+    let mut order: Vec<String> = Vec::new();
+    let mut queue = VecDeque::from([root.as_str()]);
+    while let Some(current) = queue.pop_front() {
+        for &kid in children.get(current).into_iter().flatten() {
+            order.push(kid.to_string());
+            queue.push_back(kid);
+        }
+    }
+
+    let list = run_git(&app, &["-C", &repo_path, "worktree", "list", "--porcelain"]).await?;
+    if !list.status.success() {
+        return Err(format!(
+            "git worktree list failed: {}",
+            String::from_utf8_lossy(&list.stderr)
+        ));
+    }
+    let worktree_paths: HashMap<String, String> =
+        parse_worktree_porcelain(&String::from_utf8_lossy(&list.stdout))
+            .into_iter()
+            .map(|w| (w.branch, w.path))
+            .collect();
+
+    let default_branch = detect_default_branch(&app, &repo_path).await;
+    let mut not_restacked: HashSet<String> = HashSet::new();
+    let mut results = Vec::new();
+
+    for current in order {
+        let parent = &parents[&current];
+        let mut result = RestackResult {
+            branch: current.clone(),
+            onto: parent.clone(),
+            status: RestackStatus::Skipped,
+            message: None,
+        };
+
+        if not_restacked.contains(parent) {
+            result.message = Some(format!("Parent {parent} was not restacked"));
+            not_restacked.insert(current);
+            results.push(result);
+            continue;
+        }
+
+        let Some(worktree_path) = worktree_paths.get(&current) else {
+            result.message = Some("Branch is not checked out in a worktree".to_string());
+            not_restacked.insert(current);
+            results.push(result);
+            continue;
+        };
+
+        let status = run_git(&app, &["-C", worktree_path, "status", "--porcelain"]).await?;
+        if !String::from_utf8_lossy(&status.stdout).trim().is_empty() {
+            result.message = Some("Worktree has uncommitted changes".to_string());
+            not_restacked.insert(current);
+            results.push(result);
+            continue;
+        }
+
+        let landed = parent_landed(&app, &repo_path, &current, parent, &default_branch).await?;
+        if !landed
+            && rev_parse(&app, &repo_path, &format!("refs/heads/{parent}"))
+                .await
+                .is_none()
+        {
+            result.status = RestackStatus::Failed;
+            result.message = Some(format!(
+                "Parent {parent} was deleted without landing on {default_branch}"
+            ));
+            not_restacked.insert(current);
+            results.push(result);
+            continue;
+        }
+        let onto = if landed {
+            default_branch.clone()
+        } else {
+            parent.clone()
+        };
+        result.onto = onto.clone();
+
+        let old_base = match stored_base(&app, &repo_path, &current).await? {
+            Some(base) => Some(base),
+            None => {
+                let out =
+                    run_git(&app, &["-C", &repo_path, "merge-base", &current, parent]).await?;
+                out.status
+                    .success()
+                    .then(|| String::from_utf8_lossy(&out.stdout).trim().to_string())
+            }
+        };
+        let Some(old_base) = old_base else {
+            result.status = RestackStatus::Failed;
+            result.message = Some(format!("Cannot find where {current} forked from {parent}"));
+            not_restacked.insert(current);
+            results.push(result);
+            continue;
+        };
+
+        let rebase = run_git(
+            &app,
+            &["-C", worktree_path, "rebase", "--onto", &onto, &old_base],
+        )
+        .await?;
+        if !rebase.status.success() {
+            let _ = run_git(&app, &["-C", worktree_path, "rebase", "--abort"]).await;
+            result.status = RestackStatus::Failed;
+            result.message = Some(format!(
+                "git rebase failed (aborted): {}",
+                String::from_utf8_lossy(&rebase.stderr).trim()
+            ));
+            not_restacked.insert(current);
+            results.push(result);
+            continue;
+        }
+
+        if landed {
+            clear_stack_parent(&app, &repo_path, &current).await;
+            result.status = RestackStatus::Retargeted;
+        } else {
+            record_stack_parent(&app, &repo_path, &current, parent).await?;
+            result.status = RestackStatus::Rebased;
+        }
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parents_land_only_with_evidence_of_their_commits() {
+        let merged_then_deleted = LandedEvidence {
+            remote_exists: false,
+            tracks_own_remote: true,
+            within_seen_remote: true,
+            ..Default::default()
+        };
+        assert!(merged_then_deleted.landed());
+        assert!(LandedEvidence {
+            on_default: true,
+            ..Default::default()
+        }
+        .landed());
+
+        // Never pushed: a task branch created tracking the default branch
+        let never_pushed = LandedEvidence {
+            tracks_own_remote: false,
+            ..merged_then_deleted
+        };
+        assert!(!never_pushed.landed());
+        // Commits added after the last push
+        assert!(!LandedEvidence {
+            within_seen_remote: false,
+            ..merged_then_deleted
+        }
+        .landed());
+        // Still open on the remote
+        assert!(!LandedEvidence {
+            remote_exists: true,
+            ..merged_then_deleted
+        }
+        .landed());
+
+        // A deleted parent has no upstream config left; the seen remote decides
+        assert!(deleted_parent_landed(never_pushed));
+        assert!(!deleted_parent_landed(LandedEvidence::default()));
+    }
+}
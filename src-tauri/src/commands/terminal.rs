@@ -1,3 +1,4 @@
+use super::tmux::tmux_shell_prefix;
 use tauri_plugin_shell::ShellExt;
 
 #[tauri::command]
@@ -7,7 +8,8 @@ pub async fn open_terminal(
     session: String,
 ) -> Result<(), String> {
     let user_shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
-    let tmux_cmd = format!("tmux attach -t {session}");
+    let tmux = tmux_shell_prefix(&app);
+    let tmux_cmd = format!("{tmux} attach -t {session}");
 
     match emulator.as_str() {
         "ghostty" => {
@@ -32,7 +34,7 @@ pub async fn open_terminal(
     activate
     create window with default profile
     tell current session of current window
-        write text "tmux -CC attach -t {session}"
+        write text "{tmux} -CC attach -t {session}"
     end tell
 end tell"#
             );
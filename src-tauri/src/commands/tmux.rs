@@ -1,26 +1,128 @@
-use serde::Serialize;
+use super::capture_all::capture_each;
+use super::config::{directiv_home, read_app_config};
+use super::layout::{layout_args, tmux_arg, OPT_AGENT_PANE, OPT_PANE};
+use super::logs::start_pane_log;
+use super::restore::forget_saved;
+use super::tmux_control::notify_sessions_changed;
+use super::workspace::{list_repo_dirs, read_repo_config};
+use super::worktree::{detect_default_branch, worktrees_base};
+use crate::agent_state::{detect, Detection};
+use crate::ansi::{to_spans, Span};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
-use tauri_plugin_shell::process::CommandEvent;
+use tauri::Manager;
+use tauri_plugin_shell::process::Command;
 use tauri_plugin_shell::ShellExt;
-use tokio::time::timeout;
+
+/// `tmux` in `directiv.config.json`.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TmuxConfig {
+    /// Server socket (`tmux -L <name>`) keeping task sessions apart from personal
+    /// ones. `null` uses the default server.
+    pub socket_name: Option<String>,
+}
+
+impl Default for TmuxConfig {
+    fn default() -> Self {
+        Self {
+            socket_name: Some("directiv".to_string()),
+        }
+    }
+}
+
+/// The socket of directiv's tmux server, read from the config once at startup.
+pub struct TmuxSocket(Option<String>);
+
+impl TmuxSocket {
+    /// The configured socket name, if it is one tmux and a shell can take as-is.
+    pub fn from_config() -> Self {
+        let Some(name) = read_app_config().tmux.socket_name else {
+            return Self(None);
+        };
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            log::warn!("Ignoring invalid tmux.socketName {name:?}, using the default server");
+            return Self(None);
+        }
+        Self(Some(name))
+    }
+}
+
+pub(crate) fn socket_name(app: &tauri::AppHandle) -> Option<String> {
+    app.state::<TmuxSocket>().0.clone()
+}
+
+/// A `tmux` command bound to directiv's server.
+pub(crate) fn tmux_command(app: &tauri::AppHandle) -> Command {
+    let command = app.shell().command("tmux");
+    match socket_name(app) {
+        Some(name) => command.args(["-L", &name]),
+        None => command,
+    }
+}
+
+/// `tmux` with the socket flag, for command lines run through a shell.
+pub(crate) fn tmux_shell_prefix(app: &tauri::AppHandle) -> String {
+    match socket_name(app) {
+        Some(name) => format!("tmux -L {name}"),
+        None => "tmux".to_string(),
+    }
+}
+
+/// Session user options identifying the task a session belongs to.
+const OPT_ISSUE: &str = "@directiv-issue";
+const OPT_REPO: &str = "@directiv-repo";
+const OPT_WORKTREE: &str = "@directiv-worktree";
+const OPT_AGENT: &str = "@directiv-agent";
+/// The command the agent was started with, see `start_agent`.
+pub(crate) const OPT_AGENT_COMMAND: &str = "@directiv-agent-command";
 
 #[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct TmuxSession {
     pub name: String,
     pub attached: bool,
     pub windows: u32,
     pub created: String,
+    /// Task metadata set by `tmux_create_session`; `None` on sessions created elsewhere
+    pub issue: Option<String>,
+    pub repo: Option<String>,
+    pub worktree: Option<String>,
+    pub agent: Option<String>,
+    /// Pane the agent runs in (`%N`), see `layout_args`
+    pub agent_pane: Option<String>,
+}
+
+/// Option values are listed `|`-separated, so `|` (and `%`, the escape) are
+/// percent-encoded when stored.
+pub(crate) fn encode_option(value: &str) -> String {
+    value.replace('%', "%25").replace('|', "%7C")
+}
+
+pub(crate) fn decode_option(value: &str) -> Option<String> {
+    (!value.is_empty()).then(|| value.replace("%7C", "|").replace("%25", "%"))
 }
 
 #[tauri::command]
 pub async fn tmux_list_sessions(app: tauri::AppHandle) -> Result<Vec<TmuxSession>, String> {
-    let output = app
-        .shell()
-        .command("tmux")
+    // Clients are listed in the same call so directiv's own control-mode clients
+    // (see tmux_control) don't mark every session as attached
+    let output = tmux_command(&app)
         .args([
             "list-sessions",
             "-F",
-            "#{session_name}|#{session_attached}|#{session_windows}|#{session_created}",
+            "S|#{session_name}|#{session_windows}|#{session_created}|#{@directiv-issue}|#{@directiv-repo}|#{@directiv-worktree}|#{@directiv-agent}|#{@directiv-agent-pane}",
+            ";",
+            "list-clients",
+            "-F",
+            "C|#{client_control_mode}|#{client_session}",
         ])
         .output()
         .await
@@ -38,19 +140,28 @@ pub async fn tmux_list_sessions(app: tauri::AppHandle) -> Result<Vec<TmuxSession
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
+    let attached: HashSet<&str> = stdout
+        .lines()
+        .filter_map(|line| line.strip_prefix("C|0|"))
+        .collect();
     let sessions = stdout
         .lines()
-        .filter(|line| !line.is_empty())
+        .filter_map(|line| line.strip_prefix("S|"))
         .filter_map(|line| {
-            let parts: Vec<&str> = line.splitn(4, '|').collect();
-            if parts.len() < 4 {
+            let parts: Vec<&str> = line.split('|').collect();
+            if parts.len() < 8 {
                 return None;
             }
             Some(TmuxSession {
                 name: parts[0].to_string(),
-                attached: parts[1] == "1",
-                windows: parts[2].parse().unwrap_or(0),
-                created: parts[3].to_string(),
+                attached: attached.contains(parts[0]),
+                windows: parts[1].parse().unwrap_or(0),
+                created: parts[2].to_string(),
+                issue: decode_option(parts[3]),
+                repo: decode_option(parts[4]),
+                worktree: decode_option(parts[5]),
+                agent: decode_option(parts[6]),
+                agent_pane: decode_option(parts[7]),
             })
         })
         .collect();
@@ -58,14 +169,81 @@ pub async fn tmux_list_sessions(app: tauri::AppHandle) -> Result<Vec<TmuxSession
     Ok(sessions)
 }
 
-#[tauri::command]
-pub async fn tmux_create_session(
-    app: tauri::AppHandle,
-    name: String,
-    working_dir: Option<String>,
-) -> Result<TmuxSession, String> {
-    let name: String = name
-        .chars()
+/// A pane of a directiv session.
+pub(crate) struct DirectivPane {
+    pub session: String,
+    pub issue: Option<String>,
+    /// The session's agent pane
+    pub agent_pane: Option<String>,
+    pub id: String,
+    pub window_index: u32,
+    pub window_name: String,
+    pub pid: u32,
+    /// `pane_current_command`
+    pub command: String,
+}
+
+/// Sessions directiv manages. On a dedicated socket that is every session; on the
+/// default server only the ones tagged with a task.
+pub(crate) async fn directiv_sessions(app: &tauri::AppHandle) -> Result<Vec<TmuxSession>, String> {
+    let dedicated = socket_name(app).is_some();
+    Ok(tmux_list_sessions(app.clone())
+        .await?
+        .into_iter()
+        .filter(|s| dedicated || s.issue.is_some() || s.worktree.is_some())
+        .collect())
+}
+
+/// Panes of every directiv session.
+pub(crate) async fn directiv_panes(app: &tauri::AppHandle) -> Result<Vec<DirectivPane>, String> {
+    let tags: HashMap<String, (Option<String>, Option<String>)> = directiv_sessions(app)
+        .await?
+        .into_iter()
+        .map(|s| (s.name, (s.issue, s.agent_pane)))
+        .collect();
+    if tags.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let output = tmux_command(app)
+        .args([
+            "list-panes",
+            "-a",
+            "-F",
+            "#{session_name}|#{pane_id}|#{window_index}|#{pane_pid}|#{pane_current_command}|#{window_name}",
+        ])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run tmux: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("tmux list-panes failed: {stderr}"));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(6, '|');
+            let session = parts.next()?.to_string();
+            let (issue, agent_pane) = tags.get(&session)?.clone();
+            Some(DirectivPane {
+                session,
+                issue,
+                agent_pane,
+                id: parts.next()?.to_string(),
+                window_index: parts.next()?.parse().ok()?,
+                pid: parts.next()?.parse().ok()?,
+                command: parts.next()?.to_string(),
+                window_name: parts.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Sanitize a branch name / identifier into a valid tmux session name
+/// (mirrors `toSessionName` on the frontend).
+pub(crate) fn session_name(name: &str) -> String {
+    name.chars()
         .map(|c| {
             if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
                 c
@@ -73,37 +251,189 @@ pub async fn tmux_create_session(
                 '-'
             }
         })
-        .collect();
+        .collect()
+}
 
-    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
-    let signal = format!("ready_{name}");
-    let shell_cmd = format!("{shell} -ic 'tmux wait-for -S {signal}; exec {shell}'");
+/// What a new session's environment carries beyond what the backend knows.
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SessionEnv {
+    pub issue_url: Option<String>,
+    /// Defaults to the repo's default branch
+    pub base_branch: Option<String>,
+    /// Extra variables, overriding the repo's `env`
+    pub vars: HashMap<String, String>,
+}
 
-    let mut args = vec![
-        "new-session".to_string(),
-        "-d".to_string(),
-        "-s".to_string(),
-        name.clone(),
+fn valid_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The environment of a task session: the repo's `env`, then the caller's
+/// variables, then the `DIRECTIV_*` variables, which can't be overridden.
+async fn session_env(
+    app: &tauri::AppHandle,
+    repo_env: BTreeMap<String, String>,
+    issue: Option<&str>,
+    repo: Option<&str>,
+    worktree: Option<&str>,
+    env: SessionEnv,
+) -> Result<Vec<(String, String)>, String> {
+    let mut vars: BTreeMap<String, String> = repo_env;
+    vars.extend(env.vars);
+    if let Some(name) = vars.keys().find(|name| !valid_env_name(name)) {
+        return Err(format!("Invalid environment variable name: {name:?}"));
+    }
+
+    let base_branch = match (env.base_branch, repo) {
+        (Some(branch), _) => Some(branch),
+        (None, Some(repo)) => Some(detect_default_branch(app, repo).await),
+        (None, None) => None,
+    };
+    let standard = [
+        ("DIRECTIV_ISSUE_ID", issue.map(str::to_string)),
+        ("DIRECTIV_ISSUE_URL", env.issue_url),
+        ("DIRECTIV_REPO", repo.map(str::to_string)),
+        ("DIRECTIV_WORKTREE", worktree.map(str::to_string)),
+        ("DIRECTIV_BASE_BRANCH", base_branch),
     ];
+    for (name, value) in standard {
+        match value {
+            Some(value) => vars.insert(name.to_string(), value),
+            None => vars.remove(name),
+        };
+    }
+    Ok(vars.into_iter().collect())
+}
 
-    if let Some(dir) = &working_dir {
-        args.push("-c".to_string());
-        args.push(dir.clone());
+/// Create a session for a task, tagged with its metadata and laid out after the
+/// `layout` of the repo's `.directiv.json`. Its environment is set up by
+/// [`session_env`].
+///
+/// Different branches can sanitize to the same name; when the name is taken the
+/// session gets a numeric suffix, so callers must use the returned name.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn tmux_create_session(
+    app: tauri::AppHandle,
+    name: String,
+    working_dir: Option<String>,
+    issue: Option<String>,
+    repo: Option<String>,
+    agent: Option<String>,
+    log_output: Option<bool>,
+    env: Option<SessionEnv>,
+) -> Result<TmuxSession, String> {
+    let base = session_name(&name);
+    let same_task = |session: &TmuxSession| match (&working_dir, &session.worktree) {
+        (Some(dir), Some(worktree)) => dir == worktree,
+        _ => issue.is_some() && session.issue == issue && session.repo == repo,
+    };
+    let mut listed = tmux_list_sessions(app.clone()).await?;
+    // The task already has a session: use it rather than start a second one
+    if let Some(existing) = listed.iter().find(|s| same_task(s)) {
+        return Ok(existing.clone());
     }
 
-    args.push(shell_cmd);
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
+    let repo_config = repo
+        .as_deref()
+        .map(|repo| read_repo_config(Path::new(repo)).0)
+        .unwrap_or_default();
+    let env = session_env(
+        &app,
+        repo_config.env,
+        issue.as_deref(),
+        repo.as_deref(),
+        working_dir.as_deref(),
+        env.unwrap_or_default(),
+    )
+    .await?;
 
-    let output = app
-        .shell()
-        .command("tmux")
-        .args(&args)
-        .output()
-        .await
-        .map_err(|e| format!("Failed to run tmux: {e}"))?;
+    let mut attempts = 0;
+    let (name, output) = loop {
+        // Another task holding the name gets a suffix
+        let taken: HashSet<&str> = listed.iter().map(|s| s.name.as_str()).collect();
+        let name = std::iter::once(base.clone())
+            .chain((2..).map(|n| format!("{base}-{n}")))
+            .find(|candidate| !taken.contains(candidate.as_str()))
+            .unwrap_or_else(|| base.clone());
 
-    if !output.status.success() {
+        let signal = format!("ready_{name}");
+        let tmux = tmux_shell_prefix(&app);
+        let shell_cmd = format!("{shell} -ic '{tmux} wait-for -S {signal}; exec {shell}'");
+        let mut args = layout_args(
+            &name,
+            working_dir.as_deref(),
+            repo_config.layout.as_ref(),
+            &shell_cmd,
+            &env,
+        )?;
+
+        // Tag the session in the same invocation so it is never seen without metadata
+        let target = format!("={name}");
+        let options = [
+            (OPT_ISSUE, &issue),
+            (OPT_REPO, &repo),
+            (OPT_WORKTREE, &working_dir),
+            (OPT_AGENT, &agent),
+        ];
+        for (option, value) in options {
+            if let Some(value) = value {
+                args.extend([
+                    ";".to_string(),
+                    "set-option".to_string(),
+                    "-t".to_string(),
+                    target.clone(),
+                    option.to_string(),
+                    tmux_arg(&encode_option(value)),
+                ]);
+            }
+        }
+
+        args.extend([
+            ";".to_string(),
+            "display-message".to_string(),
+            "-p".to_string(),
+            "-t".to_string(),
+            target.clone(),
+            format!("#{{{OPT_AGENT_PANE}}}"),
+        ]);
+
+        let output = tmux_command(&app)
+            .args(&args)
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run tmux: {e}"))?;
+        if output.status.success() {
+            break (name, output);
+        }
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("tmux new-session failed: {stderr}"));
+        // The name was taken since the listing, maybe by this very task
+        if !stderr.contains("duplicate session") || attempts == 5 {
+            return Err(format!("tmux new-session failed: {stderr}"));
+        }
+        attempts += 1;
+        listed = tmux_list_sessions(app.clone()).await?;
+        if let Some(existing) = listed.iter().find(|s| same_task(s)) {
+            return Ok(existing.clone());
+        }
+    };
+    notify_sessions_changed(&app);
+    let agent_pane = decode_option(String::from_utf8_lossy(&output.stdout).trim());
+
+    let logs = read_app_config().logs;
+    if let (true, Some(pane)) = (log_output.unwrap_or(logs.enabled), &agent_pane) {
+        let task = issue.as_deref().unwrap_or(&base);
+        let repo_path = repo.as_deref().unwrap_or_default();
+        // The session is usable without its log
+        if let Err(e) = start_pane_log(&app, pane, repo_path, task, &logs, logs.record).await {
+            log::warn!("Failed to start logging session {name}: {e}");
+        }
     }
 
     Ok(TmuxSession {
@@ -111,14 +441,17 @@ pub async fn tmux_create_session(
         attached: false,
         windows: 1,
         created: String::new(),
+        issue,
+        repo,
+        worktree: working_dir,
+        agent,
+        agent_pane,
     })
 }
 
 #[tauri::command]
 pub async fn tmux_kill_session(app: tauri::AppHandle, name: String) -> Result<(), String> {
-    let output = app
-        .shell()
-        .command("tmux")
+    let output = tmux_command(&app)
         .args(["kill-session", "-t", &name])
         .output()
         .await
@@ -129,7 +462,48 @@ pub async fn tmux_kill_session(app: tauri::AppHandle, name: String) -> Result<()
         return Err(format!("tmux kill-session failed: {stderr}"));
     }
 
-    Ok(())
+    // Killed on purpose: never bring it back, even if the server stops with it
+    forget_saved(&name)
+}
+
+/// The tmux target for `pane` of `session`: a pane id (`%N`) is used as-is, anything
+/// else is looked up among the panes named by the session's layout.
+pub(crate) async fn pane_target(
+    app: &tauri::AppHandle,
+    session: &str,
+    pane: Option<&str>,
+) -> Result<String, String> {
+    let Some(pane) = pane else {
+        return Ok(session.to_string());
+    };
+    if pane.starts_with('%') {
+        return Ok(pane.to_string());
+    }
+
+    let format = format!("#{{pane_id}}|#{{{OPT_PANE}}}");
+    let output = tmux_command(app)
+        .args([
+            "list-panes",
+            "-s",
+            "-t",
+            &format!("={session}"),
+            "-F",
+            &format,
+        ])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run tmux: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("tmux list-panes failed: {stderr}"));
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once('|'))
+        .find(|(_, name)| *name == pane)
+        .map(|(id, _)| id.to_string())
+        .ok_or_else(|| format!("No pane named {pane} in session {session}"))
 }
 
 #[tauri::command]
@@ -137,14 +511,16 @@ pub async fn tmux_send_keys(
     app: tauri::AppHandle,
     session: String,
     keys: String,
+    pane: Option<String>,
 ) -> Result<(), String> {
+    let target = pane_target(&app, &session, pane.as_deref()).await?;
+
     // Two-step send: literal text first, then Enter as a key name.
     // A single send-keys -l with embedded \n was attempted but reverted (d5a68f9 / e0d5b30)
     // because -l treats \n as literal text rather than a key press.
-    let output = app
-        .shell()
-        .command("tmux")
-        .args(["send-keys", "-t", &session, "-l", &keys])
+    // Multi-line text goes through tmux_paste instead.
+    let output = tmux_command(&app)
+        .args(["send-keys", "-t", &target, "-l", &keys])
         .output()
         .await
         .map_err(|e| format!("Failed to run tmux: {e}"))?;
@@ -154,10 +530,8 @@ pub async fn tmux_send_keys(
         return Err(format!("tmux send-keys failed: {stderr}"));
     }
 
-    let output = app
-        .shell()
-        .command("tmux")
-        .args(["send-keys", "-t", &session, "Enter"])
+    let output = tmux_command(&app)
+        .args(["send-keys", "-t", &target, "Enter"])
         .output()
         .await
         .map_err(|e| format!("Failed to run tmux: {e}"))?;
@@ -170,12 +544,222 @@ pub async fn tmux_send_keys(
     Ok(())
 }
 
+/// Point the `@directiv-worktree` and `@directiv-repo` tags of `session` at a
+/// worktree that moved.
+pub(crate) async fn retag_session(
+    app: &tauri::AppHandle,
+    session: &str,
+    worktree: &str,
+    repo: &str,
+) -> Result<(), String> {
+    let target = format!("={session}");
+    let output = tmux_command(app)
+        .args([
+            "set-option",
+            "-t",
+            &target,
+            OPT_WORKTREE,
+            &tmux_arg(&encode_option(worktree)),
+            ";",
+            "set-option",
+            "-t",
+            &target,
+            OPT_REPO,
+            &tmux_arg(&encode_option(repo)),
+        ])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run tmux: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("tmux set-option failed: {stderr}"));
+    }
+    Ok(())
+}
+
+/// Type the agent's start `command` into `pane` (the agent pane when omitted)
+/// and record it on the session, so a restored session can start it again.
+pub(crate) async fn start_agent(
+    app: &tauri::AppHandle,
+    session: &str,
+    command: &str,
+    pane: Option<String>,
+) -> Result<(), String> {
+    let output = tmux_command(app)
+        .args([
+            "set-option",
+            "-t",
+            &format!("={session}"),
+            OPT_AGENT_COMMAND,
+            &tmux_arg(&encode_option(command)),
+        ])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run tmux: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("tmux set-option failed: {stderr}"));
+    }
+
+    let pane = match pane {
+        Some(pane) => Some(pane),
+        None => tmux_list_sessions(app.clone())
+            .await?
+            .into_iter()
+            .find(|s| s.name == session)
+            .and_then(|s| s.agent_pane),
+    };
+    tmux_send_keys(app.clone(), session.to_string(), command.to_string(), pane).await
+}
+
 #[tauri::command]
-pub async fn tmux_capture_pane(app: tauri::AppHandle, session: String) -> Result<String, String> {
-    let output = app
-        .shell()
-        .command("tmux")
-        .args(["capture-pane", "-t", &session, "-p"])
+pub async fn tmux_start_agent(
+    app: tauri::AppHandle,
+    session: String,
+    command: String,
+    pane: Option<String>,
+) -> Result<(), String> {
+    start_agent(&app, &session, &command, pane).await
+}
+
+/// Pause between a paste and the Enter submitting it, so the application has
+/// processed the paste before the key arrives.
+const PASTE_SUBMIT_DELAY: Duration = Duration::from_millis(150);
+
+static PASTE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Paste `text` into a pane as one bracketed paste (`paste-buffer -p`), so an agent
+/// receives a multi-line prompt as a single input. The text goes through a named
+/// buffer of its own that is deleted afterwards. Trailing newlines are dropped;
+/// `submit` presses Enter after the paste.
+#[tauri::command]
+pub async fn tmux_paste(
+    app: tauri::AppHandle,
+    session: String,
+    text: String,
+    pane: Option<String>,
+    submit: Option<bool>,
+) -> Result<(), String> {
+    let target = pane_target(&app, &session, pane.as_deref()).await?;
+    let buffer = format!(
+        "directiv-paste-{}-{}",
+        std::process::id(),
+        PASTE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+
+    // load-buffer reads a file; the shell plugin gives no way to close stdin
+    let file = directiv_home()?.join(format!("{buffer}.txt"));
+    std::fs::write(&file, text.trim_end_matches('\n'))
+        .map_err(|e| format!("Failed to write {}: {e}", file.display()))?;
+    let output = tmux_command(&app)
+        .args([
+            "load-buffer",
+            "-b",
+            &buffer,
+            &file.to_string_lossy(),
+            ";",
+            "paste-buffer",
+            "-p",
+            "-d",
+            "-b",
+            &buffer,
+            "-t",
+            &target,
+        ])
+        .output()
+        .await;
+    let _ = std::fs::remove_file(&file);
+    let output = output.map_err(|e| format!("Failed to run tmux: {e}"))?;
+
+    if !output.status.success() {
+        // The buffer outlives a failed paste-buffer
+        let _ = tmux_command(&app)
+            .args(["delete-buffer", "-b", &buffer])
+            .output()
+            .await;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("tmux paste-buffer failed: {stderr}"));
+    }
+
+    if submit.unwrap_or(false) {
+        tokio::time::sleep(PASTE_SUBMIT_DELAY).await;
+        let output = tmux_command(&app)
+            .args(["send-keys", "-t", &target, "Enter"])
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run tmux: {e}"))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("tmux send-keys (Enter) failed: {stderr}"));
+        }
+    }
+
+    Ok(())
+}
+
+/// What `tmux_capture_pane` captures. The default is the visible screen as plain text.
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CaptureOptions {
+    /// First line (`-S`): 0 is the top of the screen, negative lines reach into the
+    /// scrollback, `"-"` is the start of the history
+    pub start: Option<String>,
+    /// Last line (`-E`), `"-"` being the bottom of the screen
+    pub end: Option<String>,
+    /// Keep colors and attributes as escape sequences (`-e`)
+    pub escapes: bool,
+    /// Join wrapped lines (`-J`)
+    pub join: bool,
+    /// Window (index or name) to capture the active pane of, when no pane is given
+    pub window: Option<String>,
+}
+
+fn capture_line(flag: &str, value: &str) -> Result<[String; 2], String> {
+    if value != "-" && value.parse::<i64>().is_err() {
+        return Err(format!(
+            "Invalid capture line {value:?}: expected a number or \"-\""
+        ));
+    }
+    Ok([flag.to_string(), value.to_string()])
+}
+
+/// `capture-pane` of `target` with its arguments; `window` is left to the caller.
+pub(crate) fn capture_args(target: &str, options: &CaptureOptions) -> Result<Vec<String>, String> {
+    let mut args = vec![
+        "capture-pane".to_string(),
+        "-p".to_string(),
+        "-t".to_string(),
+        target.to_string(),
+    ];
+    if let Some(start) = &options.start {
+        args.extend(capture_line("-S", start)?);
+    }
+    if let Some(end) = &options.end {
+        args.extend(capture_line("-E", end)?);
+    }
+    if options.escapes {
+        args.push("-e".to_string());
+    }
+    if options.join {
+        args.push("-J".to_string());
+    }
+    Ok(args)
+}
+
+pub(crate) async fn capture(
+    app: &tauri::AppHandle,
+    session: &str,
+    pane: Option<&str>,
+    options: &CaptureOptions,
+) -> Result<String, String> {
+    let target = match (pane, &options.window) {
+        (None, Some(window)) => format!("={session}:{window}"),
+        _ => pane_target(app, session, pane).await?,
+    };
+
+    let args = capture_args(&target, options)?;
+    let output = tmux_command(app)
+        .args(&args)
         .output()
         .await
         .map_err(|e| format!("Failed to run tmux: {e}"))?;
@@ -189,43 +773,258 @@ pub async fn tmux_capture_pane(app: tauri::AppHandle, session: String) -> Result
 }
 
 #[tauri::command]
-pub async fn tmux_wait_for_ready(
+pub async fn tmux_capture_pane(
     app: tauri::AppHandle,
     session: String,
-    timeout_ms: Option<u64>,
-) -> Result<(), String> {
-    let signal = format!("ready_{session}");
-    let timeout_duration = Duration::from_millis(timeout_ms.unwrap_or(30_000));
+    pane: Option<String>,
+    options: Option<CaptureOptions>,
+) -> Result<String, String> {
+    capture(
+        &app,
+        &session,
+        pane.as_deref(),
+        &options.unwrap_or_default(),
+    )
+    .await
+}
 
-    let (mut rx, child) = app
+/// Capture a pane with its colors, as lines of styled spans.
+#[tauri::command]
+pub async fn tmux_capture_spans(
+    app: tauri::AppHandle,
+    session: String,
+    pane: Option<String>,
+    options: Option<CaptureOptions>,
+) -> Result<Vec<Vec<Span>>, String> {
+    let options = CaptureOptions {
+        escapes: true,
+        ..options.unwrap_or_default()
+    };
+    let text = capture(&app, &session, pane.as_deref(), &options).await?;
+    Ok(to_spans(&text))
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAgentState {
+    pub session: String,
+    #[serde(flatten)]
+    pub detection: Detection,
+}
+
+/// Agent state of each session (all of them when `sessions` is omitted), read from
+/// the screen of its agent pane.
+#[tauri::command]
+pub async fn tmux_agent_states(
+    app: tauri::AppHandle,
+    sessions: Option<Vec<String>>,
+) -> Result<Vec<SessionAgentState>, String> {
+    let listed: Vec<TmuxSession> = tmux_list_sessions(app.clone())
+        .await?
+        .into_iter()
+        .filter(|s| {
+            sessions
+                .as_ref()
+                .map_or(true, |names| names.contains(&s.name))
+        })
+        .collect();
+    // Every agent pane in one tmux call; sessions gone by then are left out
+    Ok(capture_each(&app, listed, &CaptureOptions::default())
+        .await?
+        .into_iter()
+        .map(|(session, screen)| SessionAgentState {
+            session: session.name,
+            detection: detect(&screen),
+        })
+        .collect())
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AdoptedSession {
+    pub name: String,
+    /// Root of the worktree the session runs in, tagged as its `@directiv-worktree`
+    pub worktree: Option<String>,
+    pub repo: Option<String>,
+    /// Whether it can be moved without losing anything but its scrollback: a single
+    /// pane sitting at a shell prompt, inside a task worktree
+    pub movable: bool,
+    pub adopted: bool,
+    pub message: String,
+}
+
+/// The worktrees base of every workspace repo, with its repo.
+fn worktree_bases() -> Vec<(PathBuf, PathBuf)> {
+    read_app_config()
+        .workspaces
+        .iter()
+        .filter_map(|w| list_repo_dirs(Path::new(&w.path)).ok())
+        .flatten()
+        .filter_map(|repo| Some((worktrees_base(&repo).ok()?, repo)))
+        .collect()
+}
+
+/// The worktree `path` lies in (the directory right below a worktrees base), and
+/// its repo.
+fn worktree_root(bases: &[(PathBuf, PathBuf)], path: &Path) -> Option<(PathBuf, PathBuf)> {
+    bases.iter().find_map(|(base, repo)| {
+        let dir = path.strip_prefix(base).ok()?.components().next()?;
+        Some((base.join(dir), repo.clone()))
+    })
+}
+
+/// Sessions on the default server that were started in a task worktree.
+async fn legacy_task_sessions(app: &tauri::AppHandle, bases: &[(PathBuf, PathBuf)]) -> Vec<String> {
+    // Deliberately the default server: no socket flag
+    let Ok(output) = app
         .shell()
         .command("tmux")
-        .args(["wait-for", &signal])
-        .spawn()
-        .map_err(|e| format!("Failed to spawn tmux wait-for: {e}"))?;
-
-    let result = timeout(timeout_duration, async {
-        while let Some(event) = rx.recv().await {
-            if let CommandEvent::Terminated(status) = event {
-                return if status.code == Some(0) {
-                    Ok(())
-                } else {
-                    Err(format!("tmux wait-for exited with code {:?}", status.code))
-                };
-            }
-        }
-        Err("tmux wait-for process ended unexpectedly".to_string())
-    })
-    .await;
-
-    match result {
-        Ok(inner) => inner,
-        Err(_) => {
-            let _ = child.kill();
-            Err(format!(
-                "Shell init timed out after {}ms",
-                timeout_duration.as_millis()
-            ))
+        .args(["list-sessions", "-F", "#{session_name}|#{session_path}"])
+        .output()
+        .await
+    else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once('|'))
+        .filter(|(_, path)| worktree_root(bases, Path::new(path)).is_some())
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+async fn adopt_session(
+    app: &tauri::AppHandle,
+    name: &str,
+    bases: &[(PathBuf, PathBuf)],
+    preview: bool,
+) -> AdoptedSession {
+    let mut result = AdoptedSession {
+        name: name.to_string(),
+        worktree: None,
+        repo: None,
+        movable: false,
+        adopted: false,
+        message: String::new(),
+    };
+    let target = format!("={name}");
+
+    let exists_here = tmux_command(app)
+        .args(["has-session", "-t", &target])
+        .output()
+        .await
+        .is_ok_and(|out| out.status.success());
+    if exists_here {
+        result.message = "A session with this name already runs on directiv's server".into();
+        return result;
+    }
+
+    let panes = match app
+        .shell()
+        .command("tmux")
+        .args([
+            "list-panes",
+            "-s",
+            "-t",
+            &target,
+            "-F",
+            "#{pane_current_command}|#{pane_current_path}",
+        ])
+        .output()
+        .await
+    {
+        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout).to_string(),
+        _ => {
+            result.message = "Session not found on the default server".into();
+            return result;
         }
+    };
+    let panes: Vec<(&str, &str)> = panes.lines().filter_map(|l| l.split_once('|')).collect();
+
+    let Some((worktree, repo)) = panes
+        .first()
+        .and_then(|(_, path)| worktree_root(bases, Path::new(path)))
+    else {
+        result.message = "Not running in a task worktree".into();
+        return result;
+    };
+    result.worktree = Some(worktree.to_string_lossy().to_string());
+    result.repo = Some(repo.to_string_lossy().to_string());
+
+    // Windows, panes and running processes cannot move servers; leave those alone
+    if panes.len() > 1 {
+        result.message = format!(
+            "Has {} panes; only single-pane sessions are moved",
+            panes.len()
+        );
+        return result;
+    }
+    if let Some((command, _)) = panes
+        .iter()
+        .find(|(command, _)| !matches!(*command, "zsh" | "bash" | "fish" | "sh" | "-zsh" | "-bash"))
+    {
+        result.message = format!("Still running {command}; exit it to move the session");
+        return result;
+    }
+    result.movable = true;
+    if preview {
+        result.message =
+            "Will be recreated on directiv's server; its scrollback is not kept".into();
+        return result;
+    }
+
+    if let Err(e) = tmux_create_session(
+        app.clone(),
+        name.to_string(),
+        result.worktree.clone(),
+        None,
+        result.repo.clone(),
+        None,
+        None,
+        None,
+    )
+    .await
+    {
+        result.message = e;
+        return result;
+    }
+    let _ = app
+        .shell()
+        .command("tmux")
+        .args(["kill-session", "-t", &target])
+        .output()
+        .await;
+    result.adopted = true;
+    result.message = "Moved to directiv's server".into();
+    result
+}
+
+/// Move task sessions from the default tmux server onto directiv's socket.
+///
+/// tmux cannot hand a live session to another server, so each one is recreated in
+/// its worktree and the original killed. Only single-pane sessions sitting at a
+/// prompt are moved; others are reported and left alone. Without `names`, every
+/// default server session started inside a workspace worktree is considered. A
+/// preview (the default) only reports what would happen.
+#[tauri::command]
+pub async fn tmux_adopt_sessions(
+    app: tauri::AppHandle,
+    names: Option<Vec<String>>,
+    preview: Option<bool>,
+) -> Result<Vec<AdoptedSession>, String> {
+    if socket_name(&app).is_none() {
+        return Err("tmux.socketName is not set; sessions already use the default server".into());
+    }
+
+    let bases = worktree_bases();
+    let names = match names {
+        Some(names) => names,
+        None => legacy_task_sessions(&app, &bases).await,
+    };
+    let mut results = Vec::new();
+    for name in names {
+        results.push(adopt_session(&app, &name, &bases, preview.unwrap_or(true)).await);
     }
+    Ok(results)
 }
@@ -0,0 +1,234 @@
+use super::tmux::tmux_command;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+
+/// How long `%output` is coalesced per pane before being emitted.
+const OUTPUT_FLUSH_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Long-lived tmux control-mode (`tmux -C`) clients, one per session.
+///
+/// Control clients receive `%output` only for the session they are attached to, so
+/// each session gets its own client; `%sessions-changed` (sent to every client)
+/// triggers a reconcile that attaches to new sessions. Notifications are re-emitted
+/// as Tauri events so the board can update without polling:
+///
+/// - `tmux://sessions-changed` — `()`
+/// - `tmux://window-add` — [`WindowAddEvent`]
+/// - `tmux://output` — [`OutputEvent`], coalesced per pane
+/// - `tmux://exit` — [`ExitEvent`], the session's control client went away
+#[derive(Default)]
+pub struct TmuxControl {
+    started: AtomicBool,
+    clients: Mutex<HashMap<String, CommandChild>>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowAddEvent {
+    pub session: String,
+    pub window_id: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputEvent {
+    pub session: String,
+    pub pane_id: String,
+    pub data: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExitEvent {
+    pub session: String,
+    pub reason: Option<String>,
+}
+
+/// Decode the octal escapes (`\ooo`) tmux applies to `%output` data.
+fn decode_output(escaped: &str, out: &mut Vec<u8>) {
+    let bytes = escaped.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 3 < bytes.len() {
+            let digits = &bytes[i + 1..i + 4];
+            if digits.iter().all(|d| (b'0'..=b'7').contains(d)) {
+                let value = digits
+                    .iter()
+                    .fold(0u32, |acc, d| acc * 8 + u32::from(d - b'0'));
+                out.push(value as u8);
+                i += 4;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+}
+
+async fn list_session_names(app: &tauri::AppHandle) -> Vec<String> {
+    match tmux_command(app)
+        .args(["list-sessions", "-F", "#{session_name}"])
+        .output()
+        .await
+    {
+        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Attach a control client to every session that does not have one yet.
+async fn reconcile(app: &tauri::AppHandle) {
+    let control = app.state::<TmuxControl>();
+    if !control.started.load(Ordering::SeqCst) {
+        return;
+    }
+
+    for session in list_session_names(app).await {
+        // Check and insert under one lock so concurrent reconciles don't double-attach
+        let mut clients = control.clients.lock().unwrap();
+        if clients.contains_key(&session) {
+            continue;
+        }
+
+        // ignore-size keeps the invisible client from resizing the user's windows
+        let target = format!("={session}");
+        let spawned = tmux_command(app)
+            .args(["-C", "attach-session", "-f", "ignore-size", "-t", &target])
+            .spawn();
+        let (rx, child) = match spawned {
+            Ok(spawned) => spawned,
+            Err(e) => {
+                log::warn!("Failed to start tmux control client for {session}: {e}");
+                continue;
+            }
+        };
+        clients.insert(session.clone(), child);
+        drop(clients);
+
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            read_notifications(app, session, rx).await;
+        });
+    }
+}
+
+/// Ask the control clients to pick up a session created by directiv itself.
+pub(crate) fn notify_sessions_changed(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        reconcile(&app).await;
+    });
+}
+
+fn flush_output(app: &tauri::AppHandle, session: &str, pending: &mut HashMap<String, Vec<u8>>) {
+    // Decoding per batch rather than per line keeps multi-byte characters intact
+    for (pane_id, data) in pending.drain() {
+        let _ = app.emit(
+            "tmux://output",
+            OutputEvent {
+                session: session.to_string(),
+                pane_id,
+                data: String::from_utf8_lossy(&data).to_string(),
+            },
+        );
+    }
+}
+
+async fn read_notifications(
+    app: tauri::AppHandle,
+    session: String,
+    mut rx: tauri::async_runtime::Receiver<CommandEvent>,
+) {
+    let mut pending: HashMap<String, Vec<u8>> = HashMap::new();
+    let mut flush = tokio::time::interval(OUTPUT_FLUSH_INTERVAL);
+    let mut exit_reason: Option<String> = None;
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => {
+                match event {
+                    Some(CommandEvent::Stdout(line)) => {
+                        let line = String::from_utf8_lossy(&line);
+                        let line = line.trim_end_matches(['\r', '\n']);
+                        if let Some(rest) = line.strip_prefix("%output ") {
+                            if let Some((pane_id, data)) = rest.split_once(' ') {
+                                decode_output(data, pending.entry(pane_id.to_string()).or_default());
+                            }
+                        } else if line == "%sessions-changed" {
+                            let _ = app.emit("tmux://sessions-changed", ());
+                            notify_sessions_changed(&app);
+                        } else if let Some(window_id) = line.strip_prefix("%window-add ") {
+                            let _ = app.emit(
+                                "tmux://window-add",
+                                WindowAddEvent {
+                                    session: session.clone(),
+                                    window_id: window_id.trim().to_string(),
+                                },
+                            );
+                        } else if let Some(rest) = line.strip_prefix("%exit") {
+                            let reason = rest.trim();
+                            exit_reason = (!reason.is_empty()).then(|| reason.to_string());
+                        }
+                    }
+                    Some(CommandEvent::Terminated(_)) | None => break,
+                    Some(_) => {}
+                }
+            }
+            _ = flush.tick() => flush_output(&app, &session, &mut pending),
+        }
+    }
+    flush_output(&app, &session, &mut pending);
+
+    app.state::<TmuxControl>()
+        .clients
+        .lock()
+        .unwrap()
+        .remove(&session);
+    let _ = app.emit(
+        "tmux://exit",
+        ExitEvent {
+            session,
+            reason: exit_reason,
+        },
+    );
+}
+
+/// Start streaming tmux notifications as Tauri events.
+///
+/// Idempotent. Returns whether control mode is active, so the frontend knows it can
+/// stop polling.
+#[tauri::command]
+pub async fn tmux_control_start(app: tauri::AppHandle) -> Result<bool, String> {
+    app.state::<TmuxControl>()
+        .started
+        .store(true, Ordering::SeqCst);
+    reconcile(&app).await;
+    Ok(true)
+}
+
+/// Detach every control client; the frontend falls back to polling.
+#[tauri::command]
+pub async fn tmux_control_stop(app: tauri::AppHandle) -> Result<(), String> {
+    let control = app.state::<TmuxControl>();
+    control.started.store(false, Ordering::SeqCst);
+    let clients: Vec<CommandChild> = control
+        .clients
+        .lock()
+        .unwrap()
+        .drain()
+        .map(|(_, child)| child)
+        .collect();
+    for child in clients {
+        let _ = child.kill();
+    }
+    Ok(())
+}
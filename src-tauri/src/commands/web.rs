@@ -0,0 +1,413 @@
+use super::capture_all::capture_each;
+use super::config::{directiv_home, read_app_config};
+use super::tmux::{directiv_sessions, CaptureOptions};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::Manager;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{watch, Semaphore};
+
+/// Longest request head accepted.
+const MAX_REQUEST: usize = 8 * 1024;
+/// How long a client may take to send the request head.
+const HEAD_TIMEOUT: Duration = Duration::from_secs(5);
+/// Connections served at once; more are turned away.
+const MAX_CONNECTIONS: usize = 32;
+/// Session streams open at once, out of [`MAX_CONNECTIONS`].
+const MAX_STREAMS: usize = 8;
+/// How often the streamed panes are captured.
+const STREAM_INTERVAL: Duration = Duration::from_secs(1);
+/// Comment sent on an unchanged stream, so proxies and clients keep it open.
+const HEARTBEAT: Duration = Duration::from_secs(15);
+
+const PAGE: &str = include_str!("web.html");
+
+/// `webView` in `directiv.config.json`: a read-only view of the task sessions
+/// over HTTP. Read at startup.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WebViewConfig {
+    pub enabled: bool,
+    /// `host:port` to listen on; `0.0.0.0:<port>` to reach it from the LAN
+    pub address: String,
+    /// Required on every request; generated into `~/.directiv/web-token` when unset
+    pub token: Option<String>,
+}
+
+impl Default for WebViewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: "127.0.0.1:7420".to_string(),
+            token: None,
+        }
+    }
+}
+
+/// The agent pane of every directiv session by name, `None` until first captured.
+type Screens = watch::Sender<Option<HashMap<String, String>>>;
+
+/// Capture every session in one tmux call each [`STREAM_INTERVAL`] while any
+/// stream is open, so streams share the captures instead of each running tmux.
+async fn poll_screens(app: tauri::AppHandle, screens: Arc<Screens>) {
+    loop {
+        if screens.receiver_count() > 0 {
+            let captured = match directiv_sessions(&app).await {
+                Ok(sessions) => capture_each(&app, sessions, &CaptureOptions::default()).await,
+                Err(e) => Err(e),
+            };
+            // Without tmux every session has ended
+            let captured = captured.unwrap_or_default();
+            screens.send_replace(Some(
+                captured
+                    .into_iter()
+                    .map(|(session, screen)| (session.name, screen))
+                    .collect(),
+            ));
+        }
+        tokio::time::sleep(STREAM_INTERVAL).await;
+    }
+}
+
+/// The web view's URL (token included) while it is running.
+#[derive(Default)]
+pub struct WebView(Mutex<Option<String>>);
+
+/// The token in `~/.directiv/web-token`, created on first use.
+fn stored_token() -> Result<String, String> {
+    let path = directiv_home()?.join("web-token");
+    if let Ok(token) = fs::read_to_string(&path) {
+        if !token.trim().is_empty() {
+            return Ok(token.trim().to_string());
+        }
+    }
+
+    let mut bytes = [0u8; 16];
+    fs::File::open("/dev/urandom")
+        .and_then(|mut random| std::io::Read::read_exact(&mut random, &mut bytes))
+        .map_err(|e| format!("Failed to generate a token: {e}"))?;
+    let token: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(&path)
+        .and_then(|mut file| std::io::Write::write_all(&mut file, token.as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    Ok(token)
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| {
+            let h = std::str::from_utf8(h).ok()?;
+            u8::from_str_radix(h, 16).ok()
+        });
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (b'+', _) => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[derive(Debug, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    bearer: Option<String>,
+}
+
+fn parse_request(head: &str) -> Option<Request> {
+    let mut lines = head.lines();
+    let mut first = lines.next()?.split_whitespace();
+    let method = first.next()?.to_string();
+    let target = first.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (percent_decode(k), percent_decode(v)))
+        .collect();
+    let bearer = lines.find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        let value = value.trim();
+        if !name.eq_ignore_ascii_case("authorization") {
+            return None;
+        }
+        value.strip_prefix("Bearer ").map(str::to_string)
+    });
+    Some(Request {
+        method,
+        path: percent_decode(path),
+        query,
+        bearer,
+    })
+}
+
+/// Compares in constant time, so response timing doesn't leak the token.
+fn authorized(request: &Request, token: &str) -> bool {
+    let given = request
+        .bearer
+        .as_deref()
+        .or(request.query.get("token").map(String::as_str))
+        .unwrap_or_default();
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &[u8]) {
+    let head = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(body).await;
+}
+
+/// The request head, `None` when it is malformed, too long, or doesn't arrive
+/// within [`HEAD_TIMEOUT`].
+async fn read_head(stream: &mut TcpStream) -> Option<String> {
+    let read = async {
+        let mut head = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = stream.read(&mut buf).await.ok()?;
+            if n == 0 || head.len() + n > MAX_REQUEST {
+                return None;
+            }
+            head.extend_from_slice(&buf[..n]);
+        }
+        Some(String::from_utf8_lossy(&head).to_string())
+    };
+    tokio::time::timeout(HEAD_TIMEOUT, read).await.ok()?
+}
+
+/// Stream the agent pane of `name` as server-sent events: a JSON string with the
+/// screen whenever it changes, then an `end` event once the session is gone.
+async fn stream_session(
+    app: &tauri::AppHandle,
+    stream: &mut TcpStream,
+    name: &str,
+    screens: &Screens,
+) {
+    let sessions = directiv_sessions(app).await.unwrap_or_default();
+    if !sessions.iter().any(|s| s.name == name) {
+        respond(stream, "404 Not Found", "text/plain", b"No such session").await;
+        return;
+    }
+
+    let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\
+                Cache-Control: no-store\r\nConnection: keep-alive\r\n\r\n";
+    if stream.write_all(head.as_bytes()).await.is_err() {
+        return;
+    }
+    let mut screens = screens.subscribe();
+    let mut last = None;
+    let mut last_sent = Instant::now();
+    while screens.changed().await.is_ok() {
+        let screen = screens
+            .borrow_and_update()
+            .as_ref()
+            .map(|screens| screens.get(name).cloned());
+        let event = match screen {
+            // Not captured yet
+            None => None,
+            Some(None) => {
+                let _ = stream.write_all(b"event: end\ndata: {}\n\n").await;
+                return;
+            }
+            Some(Some(screen)) if last.as_ref() != Some(&screen) => {
+                let event = format!("data: {}\n\n", serde_json::Value::String(screen.clone()));
+                last = Some(screen);
+                Some(event)
+            }
+            Some(Some(_)) if last_sent.elapsed() >= HEARTBEAT => Some(":\n\n".to_string()),
+            Some(Some(_)) => None,
+        };
+        if let Some(event) = event {
+            if stream.write_all(event.as_bytes()).await.is_err() {
+                return;
+            }
+            last_sent = Instant::now();
+        }
+    }
+}
+
+async fn handle(
+    app: tauri::AppHandle,
+    mut stream: TcpStream,
+    token: Arc<String>,
+    streams: Arc<Semaphore>,
+    screens: Arc<Screens>,
+) {
+    let Some(request) = read_head(&mut stream)
+        .await
+        .as_deref()
+        .and_then(parse_request)
+    else {
+        respond(&mut stream, "400 Bad Request", "text/plain", b"Bad request").await;
+        return;
+    };
+    if request.method != "GET" {
+        respond(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            b"Read-only",
+        )
+        .await;
+        return;
+    }
+    if !authorized(&request, &token) {
+        respond(
+            &mut stream,
+            "401 Unauthorized",
+            "text/plain",
+            b"Missing or wrong token",
+        )
+        .await;
+        return;
+    }
+
+    let path: Vec<&str> = request.path.split('/').filter(|p| !p.is_empty()).collect();
+    match path.as_slice() {
+        [] => {
+            respond(
+                &mut stream,
+                "200 OK",
+                "text/html; charset=utf-8",
+                PAGE.as_bytes(),
+            )
+            .await
+        }
+        ["api", "sessions"] => match directiv_sessions(&app).await {
+            Ok(sessions) => {
+                let body = serde_json::to_vec(&sessions).unwrap_or_default();
+                respond(&mut stream, "200 OK", "application/json", &body).await;
+            }
+            Err(e) => respond(&mut stream, "502 Bad Gateway", "text/plain", e.as_bytes()).await,
+        },
+        ["api", "sessions", name, "stream"] => match streams.try_acquire() {
+            Ok(_permit) => stream_session(&app, &mut stream, name, &screens).await,
+            Err(_) => {
+                let body = b"Too many open streams";
+                respond(&mut stream, "503 Service Unavailable", "text/plain", body).await
+            }
+        },
+        _ => respond(&mut stream, "404 Not Found", "text/plain", b"Not found").await,
+    }
+}
+
+/// Serve the web view when `webView.enabled` is set.
+pub fn spawn_web_view(app: tauri::AppHandle) {
+    let config = read_app_config().web_view;
+    if !config.enabled {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        let token = match config.token.filter(|t| !t.is_empty()) {
+            Some(token) => token,
+            None => match stored_token() {
+                Ok(token) => token,
+                Err(e) => {
+                    log::warn!("Web view disabled: {e}");
+                    return;
+                }
+            },
+        };
+        let listener = match TcpListener::bind(&config.address).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::warn!("Web view failed to listen on {}: {e}", config.address);
+                return;
+            }
+        };
+        let url = format!("http://{}/?token={token}", config.address);
+        log::info!("Web view listening on {}", config.address);
+        *app.state::<WebView>().0.lock().unwrap() = Some(url);
+
+        let token = Arc::new(token);
+        let connections = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+        let streams = Arc::new(Semaphore::new(MAX_STREAMS));
+        let screens = Arc::new(watch::channel(None).0);
+        tauri::async_runtime::spawn(poll_screens(app.clone(), screens.clone()));
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    log::warn!("Web view accept failed: {e}");
+                    continue;
+                }
+            };
+            // Over the limit the connection is closed unanswered: replying could
+            // block on a client that doesn't read
+            let Ok(permit) = connections.clone().try_acquire_owned() else {
+                drop(stream);
+                continue;
+            };
+            let (app, token) = (app.clone(), token.clone());
+            let (streams, screens) = (streams.clone(), screens.clone());
+            tauri::async_runtime::spawn(async move {
+                handle(app, stream, token, streams, screens).await;
+                drop(permit);
+            });
+        }
+    });
+}
+
+/// The web view's address with its token, `None` when it isn't running.
+#[tauri::command]
+pub async fn web_view_url(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    Ok(app.state::<WebView>().0.lock().unwrap().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_authorize_by_query_or_bearer() {
+        let head = "GET /api/sessions/eng-1/stream?x=1&token=ab%2Bc HTTP/1.1\r\nHost: h\r\n\r\n";
+        let request = parse_request(head).unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/api/sessions/eng-1/stream");
+        assert!(authorized(&request, "ab+c"));
+        assert!(!authorized(&request, "ab+d"));
+        assert!(!authorized(&request, "ab+cd"));
+
+        let head = "GET / HTTP/1.1\r\nauthorization: Bearer s3cret\r\n\r\n";
+        let request = parse_request(head).unwrap();
+        assert!(authorized(&request, "s3cret"));
+        assert!(request.query.is_empty());
+        assert!(!authorized(
+            &parse_request("GET / HTTP/1.1\r\n\r\n").unwrap(),
+            "s3cret"
+        ));
+    }
+}
@@ -1,6 +1,9 @@
+use super::layout::SessionLayout;
+use super::pool::register_repo_pool;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -10,26 +13,75 @@ pub struct DiscoveredRepo {
     pub copy_paths: Vec<String>,
     pub on_start: Vec<String>,
     pub fetch_before: bool,
+    pub pool_size: usize,
     pub config_warning: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
-struct RepoConfig {
+pub(crate) struct RepoConfig {
     #[serde(default)]
-    copy_paths: Vec<String>,
+    pub copy_paths: Vec<String>,
     #[serde(default)]
-    on_start: Vec<String>,
+    pub on_start: Vec<String>,
     #[serde(default = "default_fetch_before")]
-    fetch_before: bool,
+    pub fetch_before: bool,
+    /// Number of pre-warmed worktrees to keep ready for new tasks
+    #[serde(default)]
+    pub pool_size: usize,
+    /// Windows and panes of the task's tmux session
+    #[serde(default)]
+    pub layout: Option<SessionLayout>,
+    /// Extra environment variables of the task's tmux session
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
 }
 
 fn default_fetch_before() -> bool {
     true
 }
 
+/// Read a repo's `.directiv.json`, with a warning when it exists but cannot be used.
+pub(crate) fn read_repo_config(repo: &Path) -> (RepoConfig, Option<String>) {
+    let config_path = repo.join(".directiv.json");
+    if !config_path.exists() {
+        return (RepoConfig::default(), None);
+    }
+    match fs::read_to_string(&config_path) {
+        Ok(content) => match serde_json::from_str(&content) {
+            Ok(config) => (config, None),
+            Err(e) => {
+                let msg = format!("Failed to parse {}: {e}", config_path.display());
+                log::warn!("{msg}");
+                (RepoConfig::default(), Some(msg))
+            }
+        },
+        Err(e) => {
+            let msg = format!("Failed to read {}: {e}", config_path.display());
+            log::warn!("{msg}");
+            (RepoConfig::default(), Some(msg))
+        }
+    }
+}
+
+/// Git repositories directly inside a workspace folder.
+pub(crate) fn list_repo_dirs(workspace: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(workspace)
+        .map_err(|e| format!("Failed to read workspace directory: {}", e))?;
+
+    Ok(entries
+        .flatten()
+        .map(|entry| entry.path())
+        // Skip non-directories and anything that isn't a git repo
+        .filter(|p| p.is_dir() && p.join(".git").exists())
+        .collect())
+}
+
 #[tauri::command]
-pub async fn scan_workspace(workspace_path: String) -> Result<Vec<DiscoveredRepo>, String> {
+pub async fn scan_workspace(
+    app: tauri::AppHandle,
+    workspace_path: String,
+) -> Result<Vec<DiscoveredRepo>, String> {
     let path = Path::new(&workspace_path);
 
     if !path.exists() {
@@ -43,25 +95,9 @@ pub async fn scan_workspace(workspace_path: String) -> Result<Vec<DiscoveredRepo
         ));
     }
 
-    let entries =
-        fs::read_dir(path).map_err(|e| format!("Failed to read workspace directory: {}", e))?;
-
     let mut repos = Vec::new();
 
-    for entry in entries.flatten() {
-        let entry_path = entry.path();
-
-        // Skip non-directories
-        if !entry_path.is_dir() {
-            continue;
-        }
-
-        // Check if it's a git repo
-        let git_dir = entry_path.join(".git");
-        if !git_dir.exists() {
-            continue;
-        }
-
+    for entry_path in list_repo_dirs(path)? {
         // Get repo id from folder name
         let id = entry_path
             .file_name()
@@ -72,26 +108,16 @@ pub async fn scan_workspace(workspace_path: String) -> Result<Vec<DiscoveredRepo
         let repo_path = entry_path.to_str().unwrap_or("").to_string();
 
         // Try to read .directiv.json from the repo
-        let config_path = entry_path.join(".directiv.json");
-        let (config, config_warning) = if config_path.exists() {
-            match fs::read_to_string(&config_path) {
-                Ok(content) => match serde_json::from_str(&content) {
-                    Ok(config) => (config, None),
-                    Err(e) => {
-                        let msg = format!("Failed to parse {}: {e}", config_path.display());
-                        log::warn!("{msg}");
-                        (RepoConfig::default(), Some(msg))
-                    }
-                },
-                Err(e) => {
-                    let msg = format!("Failed to read {}: {e}", config_path.display());
-                    log::warn!("{msg}");
-                    (RepoConfig::default(), Some(msg))
-                }
-            }
-        } else {
-            (RepoConfig::default(), None)
-        };
+        let (config, config_warning) = read_repo_config(&entry_path);
+
+        register_repo_pool(
+            &app,
+            &repo_path,
+            config.pool_size,
+            &config.copy_paths,
+            &config.on_start,
+            config.fetch_before,
+        );
 
         repos.push(DiscoveredRepo {
             id,
@@ -99,6 +125,7 @@ pub async fn scan_workspace(workspace_path: String) -> Result<Vec<DiscoveredRepo
             copy_paths: config.copy_paths,
             on_start: config.on_start,
             fetch_before: config.fetch_before,
+            pool_size: config.pool_size,
             config_warning,
         });
     }
@@ -1,5 +1,13 @@
+use super::agent_hooks::install_configured_agent_hooks;
+use super::config::directiv_home;
+use super::pool::{claim_pooled_worktree, pool_dir};
+use super::stack::{read_stack_parents, record_stack_parent};
+use crate::refname::{validate_branch_name, worktree_dir_name};
 use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
 use tauri_plugin_shell::ShellExt;
 
 /// Auto-detect the default branch on `origin`.
@@ -8,7 +16,7 @@ use tauri_plugin_shell::ShellExt;
 /// 2. Fallback: check if `origin/main` exists
 /// 3. Fallback: check if `origin/master` exists
 /// 4. Last resort: return `"origin/main"` (git will give a clear error)
-async fn detect_default_branch(app: &tauri::AppHandle, repo_path: &str) -> String {
+pub(crate) async fn detect_default_branch(app: &tauri::AppHandle, repo_path: &str) -> String {
     // Try symbolic-ref first (most reliable when set)
     if let Ok(out) = app
         .shell()
@@ -66,35 +74,22 @@ pub struct WorktreeInfo {
     pub is_dirty: bool,
     pub ahead: u32,
     pub behind: u32,
+    pub parent_branch: Option<String>,
+    /// The recorded path no longer exists (e.g. the workspace was moved); see `worktree_repair`
+    pub is_missing: bool,
+    /// Claimed from the worktree pool, where onStart hooks already ran
+    pub from_pool: bool,
 }
 
-#[tauri::command]
-pub async fn worktree_list(
-    app: tauri::AppHandle,
-    repo_path: String,
-) -> Result<Vec<WorktreeInfo>, String> {
-    let output = app
-        .shell()
-        .command("git")
-        .args(["-C", &repo_path, "worktree", "list", "--porcelain"])
-        .output()
-        .await
-        .map_err(|e| format!("Failed to run git: {e}"))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("git worktree list failed: {stderr}"));
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
+pub(crate) struct RawWorktree {
+    pub path: String,
+    pub branch: String,
+}
 
-    // First pass: collect basic worktree info (path + branch)
+/// Parse `git worktree list --porcelain` into (path, branch) pairs, main worktree first.
+pub(crate) fn parse_worktree_porcelain(stdout: &str) -> Vec<RawWorktree> {
     // Note: tauri-plugin-shell may insert extra blank lines in stdout,
     // so we push entries when we encounter the next "worktree" line or end of input.
-    struct RawWorktree {
-        path: String,
-        branch: String,
-    }
     let mut raw: Vec<RawWorktree> = Vec::new();
     let mut current_path = String::new();
     let mut current_branch = String::new();
@@ -130,7 +125,38 @@ pub async fn worktree_list(
         });
     }
 
-    // Second pass: enrich each worktree with health data
+    raw
+}
+
+#[tauri::command]
+pub async fn worktree_list(
+    app: tauri::AppHandle,
+    repo_path: String,
+) -> Result<Vec<WorktreeInfo>, String> {
+    let output = app
+        .shell()
+        .command("git")
+        .args(["-C", &repo_path, "worktree", "list", "--porcelain"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git worktree list failed: {stderr}"));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut raw = parse_worktree_porcelain(&stdout);
+    let parents = read_stack_parents(&app, &repo_path).await;
+
+    // Pre-warmed pool worktrees are an implementation detail, not tasks
+    if let Ok(base) = worktrees_base(Path::new(&repo_path)) {
+        let pool = pool_dir(&base);
+        raw.retain(|rw| !Path::new(&rw.path).starts_with(&pool));
+    }
+
+    // Enrich each worktree with health data and its stack parent
     let mut worktrees: Vec<WorktreeInfo> = Vec::new();
     for (i, rw) in raw.iter().enumerate() {
         let issue_id = if rw.branch.is_empty() {
@@ -139,8 +165,9 @@ pub async fn worktree_list(
             Some(rw.branch.clone())
         };
 
-        // Skip health checks for the main worktree (first entry)
-        let (is_dirty, ahead, behind) = if i == 0 {
+        // Skip health checks for the main worktree (first entry) and missing ones
+        let is_missing = !Path::new(&rw.path).exists();
+        let (is_dirty, ahead, behind) = if i == 0 || is_missing {
             (false, 0, 0)
         } else {
             get_worktree_health(&app, &rw.path, &rw.branch).await
@@ -153,6 +180,9 @@ pub async fn worktree_list(
             is_dirty,
             ahead,
             behind,
+            parent_branch: parents.get(&rw.branch).cloned(),
+            is_missing,
+            from_pool: false,
         });
     }
 
@@ -213,7 +243,20 @@ fn parse_ahead_behind(output: &str) -> (u32, u32) {
     }
 }
 
-fn validate_relative_path(rel: &str) -> Result<(), String> {
+/// Directory holding a repo's task worktrees: `<parent>/<repo>-worktrees`.
+pub(crate) fn worktrees_base(repo: &Path) -> Result<PathBuf, String> {
+    let repo_basename = repo
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("Invalid repo path")?;
+
+    Ok(repo
+        .parent()
+        .ok_or("Repo has no parent directory")?
+        .join(format!("{repo_basename}-worktrees")))
+}
+
+pub(crate) fn validate_relative_path(rel: &str) -> Result<(), String> {
     if rel.is_empty() {
         return Err("copyPaths: empty path is not allowed".to_string());
     }
@@ -235,7 +278,7 @@ fn validate_relative_path(rel: &str) -> Result<(), String> {
     Ok(())
 }
 
-fn copy_path(src: &Path, dst: &Path) -> Result<(), String> {
+pub(crate) fn copy_path(src: &Path, dst: &Path) -> Result<(), String> {
     let meta = std::fs::symlink_metadata(src)
         .map_err(|e| format!("Failed to read metadata for {}: {e}", src.display()))?;
 
@@ -288,24 +331,25 @@ pub async fn worktree_create(
     copy_paths: Option<Vec<String>>,
     base_branch: Option<String>,
     fetch_before: Option<bool>,
+    parent_branch: Option<String>,
 ) -> Result<WorktreeInfo, String> {
-    let repo = Path::new(&repo_path);
-    let repo_basename = repo
-        .file_name()
-        .and_then(|n| n.to_str())
-        .ok_or("Invalid repo path")?;
+    validate_branch_name(&issue_id)?;
+    let parent_branch = parent_branch.filter(|p| !p.is_empty());
+    if let Some(ref parent) = parent_branch {
+        validate_branch_name(parent)?;
+        if *parent == issue_id {
+            return Err(format!("A branch cannot be stacked on itself: {issue_id}"));
+        }
+    }
 
-    let worktrees_dir = format!("{}-worktrees", repo_basename);
-    let worktrees_base = repo
-        .parent()
-        .ok_or("Repo has no parent directory")?
-        .join(&worktrees_dir);
+    let repo = Path::new(&repo_path);
+    let worktrees_base = worktrees_base(repo)?;
 
     // Create the worktrees directory if it doesn't exist
     std::fs::create_dir_all(&worktrees_base)
         .map_err(|e| format!("Failed to create worktrees directory: {e}"))?;
 
-    let worktree_path = worktrees_base.join(&issue_id);
+    let worktree_path = worktrees_base.join(worktree_dir_name(&issue_id));
     let worktree_path_str = worktree_path
         .to_str()
         .ok_or("Invalid worktree path")?
@@ -382,6 +426,9 @@ pub async fn worktree_create(
                     // Valid worktree on the correct branch → return directly (idempotent)
                     let (is_dirty, ahead, behind) =
                         get_worktree_health(&app, &worktree_path_str, &issue_id).await;
+                    let parent_branch =
+                        read_stack_parents(&app, &repo_path).await.remove(&issue_id);
+                    install_configured_agent_hooks(&app, &worktree_path_str).await;
                     return Ok(WorktreeInfo {
                         branch: issue_id.clone(),
                         path: worktree_path_str,
@@ -389,6 +436,9 @@ pub async fn worktree_create(
                         is_dirty,
                         ahead,
                         behind,
+                        parent_branch,
+                        is_missing: false,
+                        from_pool: false,
                     });
                 }
             }
@@ -399,12 +449,45 @@ pub async fn worktree_create(
             .map_err(|e| format!("Cannot clean stale directory {}: {e}", worktree_path_str))?;
     }
 
-    let base = match base_branch {
-        Some(ref b) if !b.is_empty() => b.clone(),
+    // A stacked task branches from its parent task's branch instead of the default branch
+    let base = match (&parent_branch, base_branch) {
+        (Some(parent), _) => parent.clone(),
+        (None, Some(b)) if !b.is_empty() => b,
         _ => detect_default_branch(&app, &repo_path).await,
     };
 
-    // Try creating a new branch from base_branch
+    // Fast path: claim a pre-warmed worktree from the pool and move it into place
+    if claim_pooled_worktree(&app, &repo_path, &issue_id, &base, &worktree_path).await {
+        // The pool copied its copyPaths when the entry was created; copy them again so
+        // files changed since (like .env) are current. Directories are merged.
+        for rel in &validated_paths {
+            let dst = worktree_path.join(rel);
+            if std::fs::symlink_metadata(&dst).is_ok_and(|meta| !meta.is_dir()) {
+                std::fs::remove_file(&dst)
+                    .map_err(|e| format!("Failed to replace {}: {e}", dst.display()))?;
+            }
+            copy_path(&repo.join(rel), &dst)?;
+        }
+        if let Some(ref parent) = parent_branch {
+            record_stack_parent(&app, &repo_path, &issue_id, parent).await?;
+        }
+        install_configured_agent_hooks(&app, &worktree_path_str).await;
+        return Ok(WorktreeInfo {
+            branch: issue_id.clone(),
+            path: worktree_path_str,
+            issue_id: Some(issue_id),
+            is_dirty: false,
+            ahead: 0,
+            behind: 0,
+            parent_branch,
+            is_missing: false,
+            from_pool: true,
+        });
+    }
+
+    // Try creating a new branch from base_branch. Without tracking: git would
+    // otherwise set the base (`origin/main`, or the parent task's branch) as its
+    // upstream, so a plain push or pull in the worktree would target the base
     let output = app
         .shell()
         .command("git")
@@ -413,6 +496,7 @@ pub async fn worktree_create(
             &repo_path,
             "worktree",
             "add",
+            "--no-track",
             &worktree_path_str,
             "-b",
             &issue_id,
@@ -422,7 +506,8 @@ pub async fn worktree_create(
         .await
         .map_err(|e| format!("Failed to run git: {e}"))?;
 
-    if !output.status.success() {
+    let created_branch = output.status.success();
+    if !created_branch {
         let stderr = String::from_utf8_lossy(&output.stderr);
 
         // Branch already exists — checkout the existing branch instead
@@ -458,6 +543,18 @@ pub async fn worktree_create(
         copy_path(&src, &dst)?;
     }
 
+    // Remember the parent so `worktree_restack` can rebase the chain later.
+    // An existing branch keeps whatever stack metadata it already had.
+    let parent_branch = if created_branch {
+        if let Some(ref parent) = parent_branch {
+            record_stack_parent(&app, &repo_path, &issue_id, parent).await?;
+        }
+        parent_branch
+    } else {
+        read_stack_parents(&app, &repo_path).await.remove(&issue_id)
+    };
+    install_configured_agent_hooks(&app, &worktree_path_str).await;
+
     Ok(WorktreeInfo {
         branch: issue_id.clone(),
         path: worktree_path_str,
@@ -465,6 +562,9 @@ pub async fn worktree_create(
         is_dirty: false,
         ahead: 0,
         behind: 0,
+        parent_branch,
+        is_missing: false,
+        from_pool: false,
     })
 }
 
@@ -515,6 +615,7 @@ pub async fn worktree_remove(
     // Optionally delete the branch after worktree removal
     if delete_branch == Some(true) {
         if let Some(branch_name) = branch {
+            validate_branch_name(&branch_name)?;
             let del_output = app
                 .shell()
                 .command("git")
@@ -543,6 +644,7 @@ pub async fn worktree_remove(
 /// Fetch and prune remote tracking branches
 #[tauri::command]
 pub async fn git_fetch_prune(app: tauri::AppHandle, repo_path: String) -> Result<(), String> {
+    record_remote_tips(&app, &repo_path).await;
     let output = app
         .shell()
         .command("git")
@@ -567,6 +669,8 @@ pub async fn worktree_check_merged(
     repo_path: String,
     branch: String,
 ) -> Result<bool, String> {
+    validate_branch_name(&branch)?;
+
     // Method 1: Check if the remote tracking branch has been deleted
     // This handles squash-and-merge workflows where the commit hash changes
     let remote_branch = format!("origin/{}", branch);
@@ -608,3 +712,208 @@ pub async fn worktree_check_merged(
     // Exit code 0 = is ancestor (merged), non-zero = not merged
     Ok(output.status.success())
 }
+
+/// What [`branch_landed`] decides on.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct LandedEvidence {
+    /// The tip is reachable from the default branch
+    pub on_default: bool,
+    /// `origin/<branch>` exists
+    pub remote_exists: bool,
+    /// `branch.<b>.remote` is set and `branch.<b>.merge` is `refs/heads/<b>`
+    pub tracks_own_remote: bool,
+    /// The tip is reachable from `origin/<branch>` as last seen
+    pub within_seen_remote: bool,
+}
+
+impl LandedEvidence {
+    /// On the default branch, or pushed in full to a remote branch of its own that
+    /// has since been deleted (the PR was merged, then its branch removed).
+    pub(crate) fn landed(self) -> bool {
+        self.on_default
+            || (!self.remote_exists && self.tracks_own_remote && self.within_seen_remote)
+    }
+}
+
+async fn git_stdout(app: &tauri::AppHandle, args: &[&str]) -> Option<String> {
+    let output = app.shell().command("git").args(args).output().await.ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+async fn is_ancestor(
+    app: &tauri::AppHandle,
+    repo_path: &str,
+    commit: &str,
+    of: &str,
+) -> Result<bool, String> {
+    let output = app
+        .shell()
+        .command("git")
+        .args(["-C", repo_path, "merge-base", "--is-ancestor", commit, of])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+    Ok(output.status.success())
+}
+
+/// Tips of `origin`'s branches as last seen, by repo then branch. A deleted remote
+/// branch only vouches for the commits it was seen to hold.
+fn remote_tips_path() -> Result<PathBuf, String> {
+    Ok(directiv_home()?.join("remote-tips.json"))
+}
+
+static REMOTE_TIPS_LOCK: Mutex<()> = Mutex::new(());
+
+fn read_remote_tips() -> HashMap<String, HashMap<String, String>> {
+    remote_tips_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Add `tips` to the store, which only keeps the branches that still exist
+/// locally, in repos that still exist; written only when something changed.
+async fn remember_remote_tips(
+    app: &tauri::AppHandle,
+    repo_path: &str,
+    tips: HashMap<String, String>,
+) {
+    let Some(local) = git_stdout(
+        app,
+        &[
+            "-C",
+            repo_path,
+            "for-each-ref",
+            "--format=%(refname:lstrip=2)",
+            "refs/heads",
+        ],
+    )
+    .await
+    else {
+        return;
+    };
+    let local: HashSet<&str> = local.lines().collect();
+
+    // A panic while holding the lock leaves nothing half done worth refusing
+    let _lock = REMOTE_TIPS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let previous = read_remote_tips();
+    let mut all = previous.clone();
+    all.retain(|repo, _| Path::new(repo).exists());
+    let repo = all.entry(repo_path.to_string()).or_default();
+    repo.extend(tips);
+    repo.retain(|branch, _| local.contains(branch.as_str()));
+    if repo.is_empty() {
+        all.remove(repo_path);
+    }
+    if all == previous {
+        return;
+    }
+    let Ok(path) = remote_tips_path() else {
+        return;
+    };
+    let tmp = path.with_extension("json.tmp");
+    let written = serde_json::to_string(&all)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(&tmp, json).map_err(|e| e.to_string()))
+        .and_then(|()| fs::rename(&tmp, &path).map_err(|e| e.to_string()));
+    if let Err(e) = written {
+        log::warn!("Failed to write {}: {e}", path.display());
+    }
+}
+
+/// Record where `origin`'s branches point, before a prune can remove them.
+pub(crate) async fn record_remote_tips(app: &tauri::AppHandle, repo_path: &str) {
+    let Some(refs) = git_stdout(
+        app,
+        &[
+            "-C",
+            repo_path,
+            "for-each-ref",
+            "--format=%(refname:lstrip=3) %(objectname)",
+            "refs/remotes/origin",
+        ],
+    )
+    .await
+    else {
+        return;
+    };
+    let tips = refs
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .filter(|(branch, _)| *branch != "HEAD")
+        .map(|(branch, sha)| (branch.to_string(), sha.to_string()))
+        .collect();
+    remember_remote_tips(app, repo_path, tips).await;
+}
+
+/// Evidence that `tip` (the branch itself, or its last known commit when the branch
+/// is gone) has landed on `default_branch`.
+pub(crate) async fn landed_evidence(
+    app: &tauri::AppHandle,
+    repo_path: &str,
+    branch: &str,
+    tip: &str,
+    default_branch: &str,
+) -> Result<LandedEvidence, String> {
+    validate_branch_name(branch)?;
+    let remote = format!("refs/remotes/origin/{branch}");
+    let remote_tip = git_stdout(
+        app,
+        &["-C", repo_path, "rev-parse", "--verify", "--quiet", &remote],
+    )
+    .await;
+    if let Some(sha) = &remote_tip {
+        remember_remote_tips(
+            app,
+            repo_path,
+            HashMap::from([(branch.to_string(), sha.clone())]),
+        )
+        .await;
+    }
+
+    let config = |key: &str| format!("branch.{branch}.{key}");
+    let upstream_remote = git_stdout(
+        app,
+        &["-C", repo_path, "config", "--get", &config("remote")],
+    )
+    .await;
+    let upstream_merge =
+        git_stdout(app, &["-C", repo_path, "config", "--get", &config("merge")]).await;
+    let seen = read_remote_tips()
+        .get(repo_path)
+        .and_then(|tips| tips.get(branch))
+        .cloned();
+    let within_seen_remote = match &seen {
+        Some(sha) => is_ancestor(app, repo_path, tip, sha).await?,
+        None => false,
+    };
+
+    Ok(LandedEvidence {
+        on_default: is_ancestor(app, repo_path, tip, default_branch).await?,
+        remote_exists: remote_tip.is_some(),
+        tracks_own_remote: upstream_remote.is_some_and(|r| !r.is_empty() && r != ".")
+            && upstream_merge.as_deref() == Some(format!("refs/heads/{branch}").as_str()),
+        within_seen_remote,
+    })
+}
+
+/// `worktree_check_merged`, hardened for callers that act on the answer automatically,
+/// such as deleting the branch.
+///
+/// A missing remote branch is read as "merged after the PR was closed", which is also
+/// what a branch that was never pushed looks like. That signal is only trusted for a
+/// branch tracking a remote branch of its own, and only for commits that branch was
+/// seen to hold; otherwise the branch must be an ancestor of `default_branch`.
+pub(crate) async fn branch_landed(
+    app: &tauri::AppHandle,
+    repo_path: &str,
+    branch: &str,
+    default_branch: &str,
+) -> Result<bool, String> {
+    let evidence = landed_evidence(app, repo_path, branch, branch, default_branch).await?;
+    Ok(evidence.landed())
+}
@@ -1,4 +1,13 @@
+mod agent_state;
+mod agenthook;
+mod ansi;
+mod cast;
 mod commands;
+mod pipelog;
+mod refname;
+
+pub use agenthook::run as agent_hook;
+pub use pipelog::run as pipe_log;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -13,19 +22,71 @@ pub fn run() {
     }
 
     builder
+        .manage(commands::tmux::TmuxSocket::from_config())
+        .manage(commands::pool::WorktreePool::default())
+        .manage(commands::tmux_control::TmuxControl::default())
+        .manage(commands::agent_hooks::AgentHooks::default())
+        .manage(commands::pty::PtySessions::default())
+        .manage(commands::reaper::SessionReaper::default())
+        .manage(commands::web::WebView::default())
+        .manage(commands::capture_all::CaptureCursor::default())
+        .setup(|app| {
+            commands::pool::spawn_pool_refresher(app.handle().clone());
+            commands::gc::spawn_gc_scheduler(app.handle().clone());
+            commands::reaper::spawn_reaper_scheduler(app.handle().clone());
+            commands::restore::spawn_session_saver(app.handle().clone());
+            commands::agent_hooks::spawn_agent_listener(app.handle().clone());
+            commands::web::spawn_web_view(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             commands::worktree::worktree_list,
             commands::worktree::worktree_create,
             commands::worktree::worktree_remove,
             commands::worktree::worktree_check_merged,
             commands::worktree::git_fetch_prune,
+            commands::stack::worktree_restack,
+            commands::gc::worktree_gc_run,
+            commands::gc::worktree_gc_log,
+            commands::repair::worktree_repair,
+            commands::repair::workspace_repair,
             commands::tmux::tmux_list_sessions,
             commands::tmux::tmux_create_session,
             commands::tmux::tmux_kill_session,
             commands::tmux::tmux_send_keys,
+            commands::tmux::tmux_paste,
+            commands::tmux::tmux_start_agent,
+            commands::broadcast::tmux_broadcast,
+            commands::broadcast::tmux_set_labels,
+            commands::broadcast::tmux_set_broadcast_rule,
             commands::tmux::tmux_capture_pane,
-            commands::tmux::tmux_wait_for_ready,
+            commands::capture_all::tmux_capture_all,
+            commands::tmux::tmux_capture_spans,
+            commands::tmux::tmux_agent_states,
+            commands::readiness::tmux_wait_for_ready,
+            commands::resources::tmux_session_resources,
+            commands::tmux::tmux_adopt_sessions,
+            commands::restore::tmux_save_sessions,
+            commands::restore::tmux_restore_sessions,
+            commands::reaper::session_reaper_run,
+            commands::reaper::session_reaper_log,
+            commands::web::web_view_url,
+            commands::tmux_control::tmux_control_start,
+            commands::tmux_control::tmux_control_stop,
+            commands::logs::task_logs_list,
+            commands::logs::task_log_read,
+            commands::logs::task_logs_delete,
+            commands::logs::task_recordings_list,
+            commands::logs::task_recording_export,
+            commands::logs::tmux_set_recording,
+            commands::search::session_search,
+            commands::agent_hooks::agent_hooks_install,
+            commands::agent_hooks::agent_states,
             commands::terminal::open_terminal,
+            commands::pty::pty_open,
+            commands::pty::pty_write,
+            commands::pty::pty_resize,
+            commands::pty::pty_close,
             commands::terminal::open_editor,
             commands::hooks::run_hooks,
             commands::config::load_config,
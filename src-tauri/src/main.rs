@@ -2,5 +2,14 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // tmux pipe-pane runs this binary as the output sink of logged sessions,
+    // Claude Code as the relay of its hooks
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("--pipe-log") => std::process::exit(directiv_lib::pipe_log(&args[2..])),
+        Some("--agent-hook") => std::process::exit(directiv_lib::agent_hook(&args[2..])),
+        _ => {}
+    }
+
     directiv_lib::run();
 }
@@ -0,0 +1,139 @@
+//! `directiv --pipe-log <dir> [--max-bytes N] [--max-files N] [--cast WxH]`
+//!
+//! The sink tmux's `pipe-pane` feeds a pane's output into. It runs outside the app:
+//! escape sequences are stripped, output goes to `<dir>/<unix-millis>.log`, a new
+//! file is started once the current one reaches `--max-bytes`, and only the newest
+//! `--max-files` logs of the directory are kept. With `--cast`, the raw output is
+//! also recorded into `<dir>/<unix-millis>.cast` for a terminal of that size,
+//! rotated and pruned the same way.
+
+use crate::ansi::Stripper;
+use crate::cast::CastWriter;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+const DEFAULT_MAX_FILES: usize = 20;
+
+fn new_log_file(dir: &Path) -> std::io::Result<File> {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(format!("{millis}.log")))
+}
+
+/// Delete the oldest `.log` (or `.cast`) files of `dir` so at most `max_files`
+/// remain.
+pub(crate) fn prune_logs(dir: &Path, extension: &str, max_files: usize) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut logs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == extension))
+        .collect();
+    // Names are timestamps of equal width, so they sort chronologically
+    logs.sort();
+    let excess = logs.len().saturating_sub(max_files.max(1));
+    for old in &logs[..excess] {
+        let _ = fs::remove_file(old);
+    }
+}
+
+fn flag_value<T: std::str::FromStr>(args: &[String], flag: &str) -> Option<T> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .and_then(|v| v.parse().ok())
+}
+
+/// Run the sink; `args` are the arguments following `--pipe-log`. Returns the exit code.
+pub fn run(args: &[String]) -> i32 {
+    let Some(dir) = args.first().map(PathBuf::from) else {
+        eprintln!("usage: directiv --pipe-log <dir> [--max-bytes N] [--max-files N] [--cast WxH]");
+        return 2;
+    };
+    let max_bytes = flag_value(args, "--max-bytes").unwrap_or(DEFAULT_MAX_BYTES);
+    let max_files = flag_value(args, "--max-files").unwrap_or(DEFAULT_MAX_FILES);
+    let cast_size = flag_value::<String>(args, "--cast").and_then(|size| {
+        let (width, height) = size.split_once('x')?;
+        Some((width.parse::<u16>().ok()?, height.parse::<u16>().ok()?))
+    });
+
+    if let Err(e) = fs::create_dir_all(&dir) {
+        eprintln!("Failed to create {}: {e}", dir.display());
+        return 1;
+    }
+    let mut file = match new_log_file(&dir) {
+        Ok(opened) => opened,
+        Err(e) => {
+            eprintln!("Failed to open log in {}: {e}", dir.display());
+            return 1;
+        }
+    };
+    prune_logs(&dir, "log", max_files);
+    let mut cast = cast_size.and_then(|(width, height)| {
+        let cast = CastWriter::create(&dir, width, height);
+        if let Err(e) = &cast {
+            eprintln!("Failed to open recording in {}: {e}", dir.display());
+        }
+        cast.ok()
+    });
+    prune_logs(&dir, "cast", max_files);
+
+    let mut stripper = Stripper::default();
+    let mut stdin = std::io::stdin().lock();
+    let mut buf = [0u8; 8192];
+    let mut clean = Vec::with_capacity(buf.len());
+    let mut written: u64 = 0;
+
+    loop {
+        let n = match stdin.read(&mut buf) {
+            Ok(0) => return 0,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(_) => return 1,
+        };
+        clean.clear();
+        stripper.strip(&buf[..n], &mut clean);
+
+        if let (Some(recording), Some((width, height))) = (&mut cast, cast_size) {
+            if recording.written >= max_bytes {
+                match CastWriter::create(&dir, width, height) {
+                    Ok(next) => {
+                        *recording = next;
+                        prune_logs(&dir, "cast", max_files);
+                    }
+                    Err(e) => eprintln!("Failed to rotate recording in {}: {e}", dir.display()),
+                }
+            }
+            // The log carries on without the recording
+            if let Err(e) = recording.write(&buf[..n]) {
+                eprintln!("Failed to write recording in {}: {e}", dir.display());
+                cast = None;
+            }
+        }
+
+        if written >= max_bytes {
+            match new_log_file(&dir) {
+                Ok(next) => {
+                    file = next;
+                    written = 0;
+                    prune_logs(&dir, "log", max_files);
+                }
+                Err(e) => eprintln!("Failed to rotate log in {}: {e}", dir.display()),
+            }
+        }
+        if file.write_all(&clean).is_err() {
+            return 1;
+        }
+        written += clean.len() as u64;
+    }
+}
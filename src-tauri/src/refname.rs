@@ -0,0 +1,174 @@
+/// Validate a branch name against the rules of `git check-ref-format --branch`.
+///
+/// Identifiers coming from the board end up both as a branch (`git worktree add -b`)
+/// and as a directory under `<repo>-worktrees/`, so anything git would reject — or
+/// that could be mistaken for a command-line flag — is refused up front with a
+/// readable error instead of a confusing git failure.
+pub fn validate_branch_name(name: &str) -> Result<(), String> {
+    let invalid = |reason: &str| Err(format!("Invalid branch name \"{name}\": {reason}"));
+
+    if name.is_empty() {
+        return invalid("empty name");
+    }
+    if name == "@" || name == "HEAD" {
+        return invalid("reserved name");
+    }
+    if name.starts_with('-') {
+        return invalid("cannot start with '-'");
+    }
+    if let Some(c) = name.chars().find(|c| {
+        c.is_ascii_control() || matches!(c, ' ' | '~' | '^' | ':' | '?' | '*' | '[' | '\\')
+    }) {
+        return invalid(&format!("contains forbidden character {c:?}"));
+    }
+    if name.contains("..") {
+        return invalid("contains \"..\"");
+    }
+    if name.contains("@{") {
+        return invalid("contains \"@{\"");
+    }
+    if name.ends_with('.') {
+        return invalid("cannot end with '.'");
+    }
+
+    for component in name.split('/') {
+        if component.is_empty() {
+            return invalid("empty path component (leading, trailing or double '/')");
+        }
+        if component.starts_with('.') {
+            return invalid("path component cannot start with '.'");
+        }
+        if component.ends_with(".lock") {
+            return invalid("path component cannot end with \".lock\"");
+        }
+    }
+
+    Ok(())
+}
+
+/// Map a (validated) branch name to a single directory name under `<repo>-worktrees/`.
+///
+/// Nested branches such as `feat/ACQ-145` must not create nested directories, and the
+/// mapping has to stay injective: `worktree_create` deletes a directory that holds the
+/// wrong branch, so two branches sharing a directory would clobber each other.
+/// `%` and `/` are percent-encoded; plain identifiers like `ACQ-145` are unchanged.
+pub fn worktree_dir_name(branch: &str) -> String {
+    let mut out = String::with_capacity(branch.len());
+    for c in branch.chars() {
+        match c {
+            '%' => out.push_str("%25"),
+            '/' => out.push_str("%2F"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_common_branch_names() {
+        for name in [
+            "ACQ-145",
+            "feat/ACQ-145",
+            "user/nico/ACQ-145-fix-login",
+            "release-1.2",
+            "a.b",
+            "v1@2",
+            "100%-done",
+        ] {
+            assert_eq!(validate_branch_name(name), Ok(()), "{name}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_refnames() {
+        for name in [
+            "",
+            "@",
+            "HEAD",
+            "-rf",
+            "--orphan",
+            "has space",
+            "tab\there",
+            "new\nline",
+            "nul\0byte",
+            "del\x7f",
+            "tilde~1",
+            "caret^",
+            "colon:x",
+            "what?",
+            "glob*",
+            "bracket[",
+            "back\\slash",
+            "double..dot",
+            "at@{brace",
+            "ends.",
+            "ends.lock",
+            "dir.lock/x",
+            ".hidden",
+            "feat/.hidden",
+            "/leading",
+            "trailing/",
+            "double//slash",
+        ] {
+            assert!(
+                validate_branch_name(name).is_err(),
+                "{name:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_path_traversal() {
+        for name in [
+            "..",
+            "../escape",
+            "../../etc/passwd",
+            "feat/../../escape",
+            "feat/..",
+            "./here",
+            "/absolute/path",
+        ] {
+            assert!(
+                validate_branch_name(name).is_err(),
+                "{name:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn dir_name_keeps_plain_identifiers() {
+        assert_eq!(worktree_dir_name("ACQ-145"), "ACQ-145");
+        assert_eq!(worktree_dir_name("release-1.2"), "release-1.2");
+    }
+
+    #[test]
+    fn dir_name_flattens_nested_branches() {
+        assert_eq!(worktree_dir_name("feat/ACQ-145"), "feat%2FACQ-145");
+        assert_eq!(worktree_dir_name("a/b/c"), "a%2Fb%2Fc");
+        assert!(!worktree_dir_name("user/nico/ACQ-1").contains('/'));
+    }
+
+    #[test]
+    fn dir_name_is_injective() {
+        let names = ["a/b", "a%2Fb", "a%b", "a%25b", "a-b", "a--b"];
+        let dirs: std::collections::HashSet<String> =
+            names.iter().map(|n| worktree_dir_name(n)).collect();
+        assert_eq!(dirs.len(), names.len());
+    }
+
+    #[test]
+    fn dir_name_of_valid_branch_stays_inside_worktrees_dir() {
+        let base = std::path::Path::new("/repos/app-worktrees");
+        for name in ["feat/x", "a/b/c", "100%/done", "v1@2"] {
+            validate_branch_name(name).unwrap();
+            let dir = worktree_dir_name(name);
+            let joined = base.join(&dir);
+            assert_eq!(joined.parent(), Some(base), "{name}");
+            assert!(!dir.starts_with('.'), "{name}");
+        }
+    }
+}
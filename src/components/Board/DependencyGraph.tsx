@@ -23,7 +23,11 @@ import {
   useLinearMyActiveIdentifiers,
   type LinearConnectionStatus,
 } from "../../hooks/useLinear";
-import { useTmuxSessions, useClaudeSessionStates } from "../../hooks/useTmux";
+import {
+  useTmuxSessions,
+  useClaudeSessionStates,
+  useSessionResources,
+} from "../../hooks/useTmux";
 import { useGitHubMyOpenPRs } from "../../hooks/useGitHub";
 import { useAllWorktrees } from "../../hooks/useWorktrees";
 import {
@@ -48,7 +52,7 @@ import {
   H_GAP,
   V_GAP,
 } from "../../lib/graphLayout";
-import { toSessionName } from "../../lib/tmux-utils";
+import { findTaskSession } from "../../lib/tmux-utils";
 import type {
   EnrichedTask,
   TmuxSession,
@@ -122,11 +126,8 @@ function DependencyGraphInner({ onProjectsChange }: DependencyGraphProps) {
     projectsError || tasksError,
   );
   const { data: sessions } = useTmuxSessions();
-  const activeSessionNames = useMemo(
-    () => (sessions ?? []).map((s) => s.name),
-    [sessions],
-  );
-  const { data: claudeStates } = useClaudeSessionStates(activeSessionNames);
+  const { data: claudeStates } = useClaudeSessionStates(sessions ?? []);
+  const { data: resources } = useSessionResources();
   const { data: prs } = useGitHubMyOpenPRs();
   const { data: allWorktrees } = useAllWorktrees(repos);
   const { data: myActiveIdentifiers } = useLinearMyActiveIdentifiers(teamIds);
@@ -154,11 +155,11 @@ function DependencyGraphInner({ onProjectsChange }: DependencyGraphProps) {
   }, [selectedProjectId, fitView]);
 
   // Build lookup maps
-  const sessionByName = useMemo(() => {
-    const map = new Map<string, TmuxSession>();
-    for (const s of sessions ?? []) map.set(s.name, s);
-    return map;
-  }, [sessions]);
+  const sessionFor = useCallback(
+    (branch: string, worktreePath?: string | null): TmuxSession | null =>
+      findTaskSession(sessions ?? [], branch, worktreePath) ?? null,
+    [sessions],
+  );
 
   const prByBranch = useMemo(() => {
     const map = new Map<string, PullRequestInfo>();
@@ -211,13 +212,13 @@ function DependencyGraphInner({ onProjectsChange }: DependencyGraphProps) {
             worktree: wt,
             repoId: rw.repoId,
             repoPath: rw.repoPath,
-            session: sessionByName.get(toSessionName(wt.branch)) ?? null,
+            session: sessionFor(wt.branch, wt.path),
           });
         }
       }
     }
     return orphans;
-  }, [myActiveIdentifiers, allWorktrees, sessionByName]);
+  }, [myActiveIdentifiers, allWorktrees, sessionFor]);
 
   const orphanBranchNames = useMemo(
     () => orphanWorktrees.map((o) => o.worktree.branch),
@@ -275,7 +276,7 @@ function DependencyGraphInner({ onProjectsChange }: DependencyGraphProps) {
         prByBranch.get(task.identifier.toLowerCase()) ??
         (wtInfo ? prByBranch.get(wtInfo.worktree.branch.toLowerCase()) : null);
 
-      const sessionName = toSessionName(task.identifier);
+      const session = sessionFor(task.identifier, wtInfo?.worktree.path);
       return {
         id: task.id,
         type: "unifiedTask",
@@ -284,10 +285,15 @@ function DependencyGraphInner({ onProjectsChange }: DependencyGraphProps) {
           task,
           worktree: wtInfo?.worktree ?? null,
           worktreeRepoPath: wtInfo?.repoPath ?? null,
-          session: sessionByName.get(sessionName) ?? null,
+          session,
           pullRequest: pr ?? null,
           repos,
-          claudeStatus: claudeStates?.get(sessionName) ?? null,
+          claudeStatus: session
+            ? (claudeStates?.get(session.name) ?? null)
+            : null,
+          resources: session
+            ? (resources?.get(session.name) ?? null)
+            : null,
         },
         draggable: false,
       };
@@ -329,8 +335,9 @@ function DependencyGraphInner({ onProjectsChange }: DependencyGraphProps) {
     orphanWorktrees,
     worktreeByBranch,
     prByBranch,
-    sessionByName,
+    sessionFor,
     claudeStates,
+    resources,
     repos,
     resolvedTheme,
     linearIssuesByBranch,
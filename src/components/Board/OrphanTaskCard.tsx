@@ -19,7 +19,6 @@ import { useSettingsStore } from "../../stores/settingsStore";
 import type { LinearIssueStub } from "../../hooks/useLinear";
 import { useWorktreeRemove } from "../../hooks/useWorktrees";
 import { tmuxKillSession, openTerminal, openEditor } from "../../lib/tauri";
-import { toSessionName } from "../../lib/tmux-utils";
 import { useQueryClient } from "@tanstack/react-query";
 
 export type OrphanTaskNodeData = {
@@ -74,7 +73,7 @@ export function OrphanTaskCard({ data }: NodeProps<OrphanTaskNodeType>) {
     if (!session) return;
     setKillingSession(true);
     try {
-      await tmuxKillSession(toSessionName(worktree.branch));
+      await tmuxKillSession(session.name);
       queryClient.invalidateQueries({ queryKey: ["tmux", "sessions"] });
     } catch {
       // Session may already be gone
@@ -91,10 +90,12 @@ export function OrphanTaskCard({ data }: NodeProps<OrphanTaskNodeType>) {
     setConfirmingDelete(false);
 
     // Kill tmux session first if exists
-    try {
-      await tmuxKillSession(toSessionName(worktree.branch));
-    } catch {
-      // Session may not exist
+    if (session) {
+      try {
+        await tmuxKillSession(session.name);
+      } catch {
+        // Session may already be gone
+      }
     }
 
     removeWorktree.mutate(
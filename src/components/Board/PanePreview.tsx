@@ -0,0 +1,94 @@
+import type { CSSProperties } from "react";
+import { Loader2 } from "lucide-react";
+import type { AnsiColor, StyledSpan, TmuxSession } from "../../types";
+import { useTmuxPanePreview } from "../../hooks/useTmux";
+
+// The 16 base colors follow the app theme; 256-color and true colors arrive as rgb
+const PALETTE = [
+  "var(--text-muted)",
+  "var(--accent-red)",
+  "var(--accent-green)",
+  "var(--accent-yellow)",
+  "var(--accent-blue)",
+  "var(--accent-purple)",
+  "#0891b2",
+  "var(--text-secondary)",
+  "var(--text-muted)",
+  "var(--accent-red)",
+  "var(--accent-green)",
+  "var(--accent-amber)",
+  "var(--accent-blue)",
+  "var(--accent-purple)",
+  "#06b6d4",
+  "var(--text-primary)",
+];
+
+function cssColor(color: AnsiColor | null): string | undefined {
+  if (!color) return undefined;
+  return "palette" in color ? PALETTE[color.palette] : color.rgb;
+}
+
+function spanStyle(span: StyledSpan): CSSProperties {
+  let color = cssColor(span.fg);
+  let background = cssColor(span.bg);
+  if (span.inverse) {
+    [color, background] = [
+      background ?? "var(--bg-primary)",
+      color ?? "var(--text-primary)",
+    ];
+  }
+  const decorations = [
+    span.underline && "underline",
+    span.strikethrough && "line-through",
+  ].filter(Boolean);
+  return {
+    color,
+    background,
+    fontWeight: span.bold ? 600 : undefined,
+    fontStyle: span.italic ? "italic" : undefined,
+    opacity: span.dim ? 0.6 : undefined,
+    textDecoration: decorations.length ? decorations.join(" ") : undefined,
+  };
+}
+
+interface PanePreviewProps {
+  session: TmuxSession;
+  lines?: number;
+}
+
+export function PanePreview({ session, lines = 30 }: PanePreviewProps) {
+  const { data, isLoading } = useTmuxPanePreview(session);
+
+  if (isLoading) {
+    return (
+      <div className="flex justify-center p-3">
+        <Loader2 className="size-4 animate-spin text-[var(--text-muted)]" />
+      </div>
+    );
+  }
+
+  // Drop the empty screen rows below the cursor, then keep the tail
+  const rows = [...(data ?? [])];
+  while (
+    rows.length > 0 &&
+    rows[rows.length - 1].every((s) => !s.text.trim())
+  ) {
+    rows.pop();
+  }
+
+  return (
+    <pre className="max-h-72 overflow-auto whitespace-pre p-2 font-mono text-[10px] leading-tight text-[var(--text-primary)]">
+      {rows.slice(-lines).map((row, i) => (
+        <div key={i}>
+          {row.length === 0
+            ? " "
+            : row.map((span, j) => (
+                <span key={j} style={spanStyle(span)}>
+                  {span.text}
+                </span>
+              ))}
+        </div>
+      ))}
+    </pre>
+  );
+}
@@ -0,0 +1,105 @@
+import { useEffect, useRef, useState } from "react";
+import { createPortal } from "react-dom";
+import { X } from "lucide-react";
+import { Terminal } from "@xterm/xterm";
+import { FitAddon } from "@xterm/addon-fit";
+import "@xterm/xterm/css/xterm.css";
+import { usePty } from "../../hooks/usePty";
+import type { TmuxSession } from "../../types";
+
+interface TerminalPanelProps {
+  session: TmuxSession;
+  onClose: () => void;
+}
+
+function themeColor(name: string, fallback: string) {
+  const value = getComputedStyle(document.documentElement)
+    .getPropertyValue(name)
+    .trim();
+  return value || fallback;
+}
+
+/**
+ * The task's tmux session attached inside directiv. Closing detaches; the
+ * session keeps running.
+ */
+export function TerminalPanel({ session, onClose }: TerminalPanelProps) {
+  const containerRef = useRef<HTMLDivElement>(null);
+  const terminalRef = useRef<Terminal | null>(null);
+  // Attach only once the terminal is fitted, so tmux starts at the right size
+  const [size, setSize] = useState<{ cols: number; rows: number } | null>(
+    null,
+  );
+
+  const { write } = usePty(
+    size ? session.name : null,
+    size?.cols ?? 80,
+    size?.rows ?? 24,
+    {
+      onData: (data) => terminalRef.current?.write(data),
+      onExit: onClose,
+    },
+  );
+
+  useEffect(() => {
+    const container = containerRef.current;
+    if (!container) return;
+
+    const terminal = new Terminal({
+      fontFamily: "ui-monospace, Menlo, monospace",
+      fontSize: 12,
+      cursorBlink: true,
+      theme: {
+        background: themeColor("--bg-primary", "#0f1115"),
+        foreground: themeColor("--text-primary", "#d7dae0"),
+      },
+    });
+    const fit = new FitAddon();
+    terminal.loadAddon(fit);
+    terminal.open(container);
+    terminalRef.current = terminal;
+
+    const resize = () => {
+      fit.fit();
+      setSize({ cols: terminal.cols, rows: terminal.rows });
+    };
+    resize();
+    terminal.focus();
+    const observer = new ResizeObserver(resize);
+    observer.observe(container);
+
+    return () => {
+      observer.disconnect();
+      terminalRef.current = null;
+      terminal.dispose();
+    };
+  }, []);
+
+  useEffect(() => {
+    const terminal = terminalRef.current;
+    if (!terminal) return;
+    const input = terminal.onData(write);
+    return () => input.dispose();
+  }, [write]);
+
+  return createPortal(
+    <div className="fixed inset-0 z-50 flex flex-col bg-black/60 p-6">
+      <div className="flex min-h-0 flex-1 flex-col rounded-md border border-[var(--border-default)] bg-[var(--bg-primary)] shadow-lg">
+        <div className="flex items-center justify-between border-b border-[var(--border-default)] px-3 py-1.5">
+          <span className="font-mono text-xs text-[var(--text-secondary)]">
+            {session.issue || session.name}
+          </span>
+          <button
+            onClick={onClose}
+            className="rounded p-1 text-[var(--text-muted)] hover:text-[var(--text-primary)]"
+            title="Detach (the session keeps running)"
+          >
+            <X className="size-4" />
+          </button>
+        </div>
+        <div ref={containerRef} className="min-h-0 flex-1 p-2" />
+      </div>
+    </div>,
+    document.body,
+  );
+}
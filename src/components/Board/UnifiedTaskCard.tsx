@@ -18,6 +18,9 @@ import {
   Code2,
   AlertTriangle,
   ClipboardList,
+  Eye,
+  Cpu,
+  SquareTerminal,
 } from "lucide-react";
 import { useQueryClient } from "@tanstack/react-query";
 import type {
@@ -27,8 +30,11 @@ import type {
   TmuxSession,
   DiscoveredRepo,
   ClaudeSessionStatus,
+  SessionResources,
 } from "../../types";
 import { CIStatusIcon } from "./CIStatusIcon";
+import { PanePreview } from "./PanePreview";
+import { TerminalPanel } from "./TerminalPanel";
 import { useStartTask } from "../../hooks/useStartTask";
 import { SKILLS, type Skill } from "../../lib/workflows";
 import { useSettingsStore } from "../../stores/settingsStore";
@@ -100,6 +106,11 @@ function getWorkflowStatus(
   return "personal-review";
 }
 
+function formatMemory(kb: number): string {
+  if (kb >= 1024 * 1024) return `${(kb / 1024 / 1024).toFixed(1)} GB`;
+  return `${Math.round(kb / 1024)} MB`;
+}
+
 export type UnifiedTaskNodeData = {
   task: EnrichedTask;
   worktree: WorktreeInfo | null;
@@ -108,6 +119,7 @@ export type UnifiedTaskNodeData = {
   pullRequest: PullRequestInfo | null;
   repos: DiscoveredRepo[];
   claudeStatus: ClaudeSessionStatus | null;
+  resources: SessionResources | null;
   onDragStart?: (nodeId: string, e: React.MouseEvent) => void;
   isBeingTargeted?: boolean;
 };
@@ -123,6 +135,7 @@ export function UnifiedTaskCard({ id, data }: NodeProps<UnifiedTaskNodeType>) {
     pullRequest,
     repos,
     claudeStatus,
+    resources,
     onDragStart,
     isBeingTargeted,
   } = data;
@@ -136,6 +149,8 @@ export function UnifiedTaskCard({ id, data }: NodeProps<UnifiedTaskNodeType>) {
   const [deletingWorktree, setDeletingWorktree] = useState(false);
   const [confirmingDelete, setConfirmingDelete] = useState(false);
   const [dropdownOpen, setDropdownOpen] = useState(false);
+  const [previewOpen, setPreviewOpen] = useState(false);
+  const [attached, setAttached] = useState(false);
   const [selectedRepo, setSelectedRepo] = useState<DiscoveredRepo | null>(null);
   const [pendingSkill, setPendingSkill] = useState<Skill>(SKILLS.CODE);
   const dropdownRef = useRef<HTMLDivElement>(null);
@@ -144,9 +159,21 @@ export function UnifiedTaskCard({ id, data }: NodeProps<UnifiedTaskNodeType>) {
   const isLoading = startTask.isPending || killingSession || deletingWorktree;
   const workflowStatus = getWorkflowStatus(session, pullRequest);
   const statusLabel = WORKFLOW_LABELS[workflowStatus];
+  const agentNeedsInput =
+    claudeStatus === "idle" ||
+    claudeStatus === "permission" ||
+    claudeStatus === "error";
   const needsInput =
     workflowStatus === "personal-review" ||
-    (claudeStatus === "waiting" && workflowStatus === "in-dev");
+    (agentNeedsInput && workflowStatus === "in-dev");
+  const needsInputLabel =
+    workflowStatus !== "in-dev"
+      ? "Needs Input"
+      : claudeStatus === "permission"
+        ? "Needs Approval"
+        : claudeStatus === "error"
+          ? "Agent Error"
+          : "Needs Input";
 
   useEffect(() => {
     if (!confirmingDelete) return;
@@ -219,6 +246,7 @@ export function UnifiedTaskCard({ id, data }: NodeProps<UnifiedTaskNodeType>) {
       {
         issueId: task.id,
         identifier: task.identifier,
+        issueUrl: task.url,
         repoPath,
         terminal,
         copyPaths: repo?.copyPaths,
@@ -226,6 +254,7 @@ export function UnifiedTaskCard({ id, data }: NodeProps<UnifiedTaskNodeType>) {
         baseBranch,
         fetchBefore: repo?.fetchBefore,
         skill: pendingSkill,
+        blockedBy: task.blockedBy.map((b) => b.identifier),
       },
       {
         onError: (err) => toastError(err),
@@ -310,7 +339,7 @@ export function UnifiedTaskCard({ id, data }: NodeProps<UnifiedTaskNodeType>) {
           {needsInput && (
             <span className="ml-auto flex items-center gap-1 animate-pulse rounded px-1.5 py-0.5 text-[10px] font-medium bg-[var(--accent-red)]/20 text-[var(--accent-red)]">
               <AlertTriangle className="size-3" />
-              Needs Input
+              {needsInputLabel}
             </span>
           )}
         </div>
@@ -374,6 +403,32 @@ export function UnifiedTaskCard({ id, data }: NodeProps<UnifiedTaskNodeType>) {
         </div>
       )}
 
+      {/* Resource use of the session's processes */}
+      {resources && (
+        <div
+          className="flex items-center gap-2 border-b border-[var(--border-default)] px-3 py-1.5 text-xs text-[var(--text-muted)]"
+          title={
+            resources.panes
+              .flatMap((p) => (p.busiest ? [p.busiest] : []))
+              .map((p) => `${p.name} (${p.pid}): ${Math.round(p.cpuPercent)}%`)
+              .join("\n") || undefined
+          }
+        >
+          <Cpu className="size-3.5 shrink-0" />
+          <span>{Math.round(resources.cpuPercent)}%</span>
+          <span>{formatMemory(resources.rssKb)}</span>
+          <span>
+            {resources.processes}{" "}
+            {resources.processes === 1 ? "process" : "processes"}
+          </span>
+          {resources.foreground && (
+            <span className="ml-auto truncate font-mono">
+              {resources.foreground}
+            </span>
+          )}
+        </div>
+      )}
+
       {/* Actions */}
       {!isDisabled && (
         <div
@@ -427,6 +482,28 @@ export function UnifiedTaskCard({ id, data }: NodeProps<UnifiedTaskNodeType>) {
             </button>
           )}
 
+          {/* Attach the session inside directiv */}
+          {hasSession && (
+            <button
+              onClick={() => setAttached(true)}
+              className="flex items-center gap-1 rounded bg-[var(--bg-elevated)] px-2 py-1 text-xs font-medium text-[var(--text-primary)] hover:opacity-80"
+              title="Attach the session here"
+            >
+              <SquareTerminal className="size-3.5" />
+            </button>
+          )}
+
+          {/* Pane preview toggle */}
+          {hasSession && (
+            <button
+              onClick={() => setPreviewOpen((open) => !open)}
+              className="flex items-center gap-1 rounded bg-[var(--bg-elevated)] px-2 py-1 text-xs font-medium text-[var(--text-primary)] hover:opacity-80"
+              title="Preview agent output"
+            >
+              <Eye className="size-3.5" />
+            </button>
+          )}
+
           {/* Editor button */}
           {worktree && (
             <button
@@ -487,6 +564,20 @@ export function UnifiedTaskCard({ id, data }: NodeProps<UnifiedTaskNodeType>) {
             </span>
           )}
 
+          {/* Live preview of the agent's pane */}
+          {previewOpen && session && (
+            <div className="absolute left-0 top-full z-20 mt-1 w-[36rem] rounded-md border border-[var(--border-default)] bg-[var(--bg-primary)] shadow-lg">
+              <PanePreview session={session} />
+            </div>
+          )}
+
+          {attached && session && (
+            <TerminalPanel
+              session={session}
+              onClose={() => setAttached(false)}
+            />
+          )}
+
           {/* Dropdown for repo/branch selection */}
           {dropdownOpen && (
             <div className="absolute left-0 top-full z-20 mt-1 rounded-md border border-[var(--border-default)] bg-[var(--bg-tertiary)] py-1 shadow-lg">
@@ -0,0 +1,146 @@
+import { useState } from "react";
+import { FolderSync, Loader2, TerminalSquare } from "lucide-react";
+import { toast } from "sonner";
+import {
+  useTmuxAdoptSessions,
+  useTmuxAdoptionPreview,
+} from "../../hooks/useTmux";
+import { useSettingsStore } from "../../stores/settingsStore";
+import { useWorkspaceStore } from "../../stores/workspaceStore";
+
+export function MaintenanceSection() {
+  return (
+    <div className="max-w-3xl space-y-6">
+      <div>
+        <h1 className="text-xl font-semibold text-[var(--text-primary)]">
+          Maintenance
+        </h1>
+        <p className="mt-1 text-sm text-[var(--text-muted)]">
+          One-off fixes, run only when you ask for them.
+        </p>
+      </div>
+      <RepairWorktrees />
+      <AdoptSessions />
+    </div>
+  );
+}
+
+function RepairWorktrees() {
+  const workspaces = useSettingsStore((s) => s.config.workspaces);
+  const repairWorkspaces = useWorkspaceStore((s) => s.repairWorkspaces);
+  const isRepairing = useWorkspaceStore((s) => s.isRepairing);
+
+  return (
+    <section>
+      <div className="mb-3 flex items-center gap-2">
+        <FolderSync className="size-4 text-[var(--accent-blue)]" />
+        <h2 className="text-sm font-medium text-[var(--text-secondary)]">
+          Moved worktrees
+        </h2>
+      </div>
+      <p className="mb-3 text-sm text-[var(--text-muted)]">
+        Reconnects repositories with their worktrees after a workspace or repo
+        was moved or renamed. Runs once at launch.
+      </p>
+      <button
+        onClick={() => repairWorkspaces(workspaces)}
+        disabled={isRepairing || workspaces.length === 0}
+        className="flex items-center gap-2 rounded border border-[var(--border-default)] px-3 py-1 text-sm text-[var(--text-primary)] hover:bg-[var(--bg-tertiary)]"
+      >
+        {isRepairing && <Loader2 className="size-3 animate-spin" />}
+        Repair worktrees
+      </button>
+    </section>
+  );
+}
+
+function AdoptSessions() {
+  const { data: sessions, isLoading } = useTmuxAdoptionPreview();
+  const adopt = useTmuxAdoptSessions();
+  const [confirming, setConfirming] = useState(false);
+
+  const movable = (sessions ?? []).filter((s) => s.movable);
+
+  const move = () => {
+    setConfirming(false);
+    adopt.mutate(movable.map((s) => s.name), {
+      onSuccess: (results) => {
+        const moved = results.filter((r) => r.adopted).length;
+        const failed = results.length - moved;
+        if (failed > 0) {
+          toast.warning(`Moved ${moved} session(s), ${failed} failed`);
+        } else {
+          toast.success(`Moved ${moved} session(s) to directiv`);
+        }
+      },
+      onError: (err) => toast.error(`Failed to move sessions: ${err}`),
+    });
+  };
+
+  return (
+    <section>
+      <div className="mb-3 flex items-center gap-2">
+        <TerminalSquare className="size-4 text-[var(--accent-blue)]" />
+        <h2 className="text-sm font-medium text-[var(--text-secondary)]">
+          Task sessions on the default tmux server
+        </h2>
+      </div>
+      {isLoading ? (
+        <Loader2 className="size-4 animate-spin text-[var(--text-muted)]" />
+      ) : !sessions?.length ? (
+        <p className="text-sm text-[var(--text-muted)]">
+          None; every task session runs on directiv's server.
+        </p>
+      ) : (
+        <div className="space-y-3">
+          <ul className="space-y-1 text-sm">
+            {sessions.map((s) => (
+              <li key={s.name} className="flex gap-2">
+                <span className="font-mono text-[var(--text-primary)]">
+                  {s.name}
+                </span>
+                <span
+                  className={
+                    s.movable
+                      ? "text-[var(--text-muted)]"
+                      : "text-[var(--accent-yellow)]"
+                  }
+                >
+                  {s.message}
+                </span>
+              </li>
+            ))}
+          </ul>
+          {movable.length > 0 &&
+            (confirming ? (
+              <div className="flex items-center gap-2 text-sm">
+                <span className="text-[var(--text-secondary)]">
+                  The sessions are recreated and their scrollback is lost.
+                </span>
+                <button
+                  onClick={move}
+                  className="rounded bg-[var(--accent-blue)] px-3 py-1 text-white"
+                >
+                  Move
+                </button>
+                <button
+                  onClick={() => setConfirming(false)}
+                  className="rounded px-3 py-1 text-[var(--text-muted)]"
+                >
+                  Cancel
+                </button>
+              </div>
+            ) : (
+              <button
+                onClick={() => setConfirming(true)}
+                disabled={adopt.isPending}
+                className="rounded border border-[var(--border-default)] px-3 py-1 text-sm text-[var(--text-primary)] hover:bg-[var(--bg-tertiary)]"
+              >
+                Move {movable.length} session(s) to directiv's server
+              </button>
+            ))}
+        </div>
+      )}
+    </section>
+  );
+}
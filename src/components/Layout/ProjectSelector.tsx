@@ -41,7 +41,7 @@ import type {
 } from "../../types";
 import { useGitHubReviewRequests } from "../../hooks/useGitHub";
 import { useStartFreeTask } from "../../hooks/useStartTask";
-import { toSessionName } from "../../lib/tmux-utils";
+import { findTaskSession } from "../../lib/tmux-utils";
 import { WorkspaceSelector } from "./WorkspaceSelector";
 
 interface ProjectSelectorProps {
@@ -427,14 +427,16 @@ function OrphanSessionsSection() {
   const scanForOrphanSessions = useCallback(async () => {
     setScanning(true);
     try {
-      // 1. Collect all branches from worktrees
+      // 1. Collect all branches and paths from worktrees
       const allBranches = new Set<string>();
+      const allPaths = new Set<string>();
       for (const repo of repos) {
         try {
           const worktrees = await worktreeList(repo.path);
           // Skip main worktree (index 0)
           for (const wt of worktrees.slice(1)) {
             allBranches.add(wt.branch.toLowerCase());
+            allPaths.add(wt.path);
           }
         } catch {
           // Skip repos that fail
@@ -445,8 +447,10 @@ function OrphanSessionsSection() {
       const sessions = await tmuxListSessions();
 
       // 3. Find orphan sessions (sessions without corresponding worktree)
-      const orphans = sessions.filter(
-        (s) => !allBranches.has(s.name.toLowerCase()),
+      const orphans = sessions.filter((s) =>
+        s.worktree
+          ? !allPaths.has(s.worktree)
+          : !allBranches.has(s.name.toLowerCase()),
       );
 
       setOrphanSessions(orphans);
@@ -611,14 +615,18 @@ function CleanupSection() {
   const cleanSelected = useCallback(async () => {
     setCleaning(true);
     try {
+      const sessions = await tmuxListSessions();
       for (const sw of staleWorktrees) {
         const key = `${sw.repoPath}:${sw.worktree.branch}`;
         if (!selected.has(key)) continue;
-        // Kill tmux session if one exists for this branch
-        try {
-          await tmuxKillSession(toSessionName(sw.worktree.branch));
-        } catch {
-          // Session may not exist
+        // Kill tmux session if one exists for this worktree
+        const session = findTaskSession(
+          sessions,
+          sw.worktree.branch,
+          sw.worktree.path,
+        );
+        if (session) {
+          await tmuxKillSession(session.name).catch(() => {});
         }
         // Remove worktree + delete branch
         await worktreeRemove(
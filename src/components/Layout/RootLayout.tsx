@@ -3,6 +3,12 @@ import { Outlet } from "@tanstack/react-router";
 import { Toaster } from "sonner";
 import { useSettingsStore } from "../../stores/settingsStore";
 import { useWorkspaceInit } from "../../hooks/useWorkspace";
+import {
+  useAgentHookEvents,
+  useIdleSessionEvents,
+  useTmuxEvents,
+  useTmuxRestore,
+} from "../../hooks/useTmux";
 
 export function RootLayout() {
   const loadFromDisk = useSettingsStore((s) => s.loadFromDisk);
@@ -16,6 +22,12 @@ export function RootLayout() {
   // Initialize workspaces after config is loaded
   useWorkspaceInit();
 
+  // Refresh tmux queries from control-mode events instead of polling
+  useTmuxEvents();
+  useTmuxRestore();
+  useAgentHookEvents();
+  useIdleSessionEvents();
+
   if (!isLoaded) {
     return (
       <div className="flex h-screen items-center justify-center bg-[var(--bg-primary)] text-[var(--text-muted)]">
@@ -0,0 +1,87 @@
+import { useCallback, useEffect, useRef } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { ptyClose, ptyOpen, ptyResize, ptyWrite } from "../lib/tauri";
+import type { PtyExitEvent, PtyOutputEvent } from "../types";
+
+interface PtyHandlers {
+  onData: (data: string) => void;
+  onExit?: (code: number | null) => void;
+}
+
+/**
+ * Attach `session` in an embedded terminal for as long as it is set; output goes
+ * to `onData` and changes of `cols`/`rows` resize it. Returns `write` for input.
+ */
+export function usePty(
+  session: string | null,
+  cols: number,
+  rows: number,
+  handlers: PtyHandlers,
+) {
+  const idRef = useRef<number | null>(null);
+  const handlersRef = useRef(handlers);
+  handlersRef.current = handlers;
+  // Only the initial size is used to open; later changes go through resize
+  const sizeRef = useRef({ cols, rows });
+  sizeRef.current = { cols, rows };
+
+  useEffect(() => {
+    if (!session) return;
+    let closed = false;
+    // Output can arrive before ptyOpen resolves with the id
+    const early: PtyOutputEvent[] = [];
+
+    const unlisteners = [
+      listen<PtyOutputEvent>("pty://output", ({ payload }) => {
+        if (idRef.current === null) early.push(payload);
+        else if (payload.id === idRef.current) {
+          handlersRef.current.onData(payload.data);
+        }
+      }),
+      listen<PtyExitEvent>("pty://exit", ({ payload }) => {
+        if (payload.id !== idRef.current) return;
+        idRef.current = null;
+        handlersRef.current.onExit?.(payload.code);
+      }),
+    ];
+
+    Promise.all(unlisteners)
+      .then(() =>
+        ptyOpen(session, sizeRef.current.cols, sizeRef.current.rows),
+      )
+      .then((id) => {
+        if (closed) {
+          ptyClose(id).catch(() => {});
+          return;
+        }
+        idRef.current = id;
+        for (const event of early) {
+          if (event.id === id) handlersRef.current.onData(event.data);
+        }
+      })
+      .catch((err) => {
+        handlersRef.current.onData(`\r\n${String(err)}\r\n`);
+      });
+
+    return () => {
+      closed = true;
+      if (idRef.current !== null) ptyClose(idRef.current).catch(() => {});
+      idRef.current = null;
+      for (const unlisten of unlisteners) {
+        unlisten.then((fn) => fn());
+      }
+    };
+  }, [session]);
+
+  useEffect(() => {
+    if (idRef.current !== null) {
+      ptyResize(idRef.current, cols, rows).catch(() => {});
+    }
+  }, [cols, rows]);
+
+  const write = useCallback((data: string) => {
+    if (idRef.current !== null) ptyWrite(idRef.current, data).catch(() => {});
+  }, []);
+
+  return { write };
+}
@@ -1,20 +1,187 @@
-import { useRef } from "react";
+import { useEffect, useMemo, useRef } from "react";
 import { useQuery, useMutation, useQueryClient } from "@tanstack/react-query";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
 import {
   tmuxListSessions,
   tmuxCreateSession,
   tmuxKillSession,
   tmuxCapturePane,
+  tmuxCaptureSpans,
+  tmuxControlStart,
+  tmuxAdoptSessions,
+  tmuxRestoreSessions,
+  tmuxAgentStates,
+  tmuxSessionResources,
+  agentStates,
 } from "../lib/tauri";
-import { detectClaudeStates } from "../lib/claudeState";
-import type { TmuxSession, ClaudeSessionStatus } from "../types";
-import { LOCAL_REFRESH_INTERVAL } from "../constants/intervals";
+import type {
+  TmuxSession,
+  ClaudeSessionStatus,
+  HookState,
+  TaskAgentState,
+  ReapDecision,
+  AdoptedSession,
+  TmuxOutputEvent,
+  SessionResources,
+  StyledSpan,
+} from "../types";
+import {
+  LOCAL_REFRESH_INTERVAL,
+  LOCAL_REFRESH_INTERVAL_SLOW,
+} from "../constants/intervals";
+
+const OUTPUT_REFRESH_THROTTLE = 1_000;
+
+/** Whether the backend's tmux control-mode clients are streaming events. */
+export function useTmuxControl() {
+  const { data } = useQuery<boolean>({
+    queryKey: ["tmux", "control"],
+    queryFn: () => tmuxControlStart().catch(() => false),
+    staleTime: Infinity,
+  });
+  return data ?? false;
+}
+
+/**
+ * Turn tmux control-mode events into query invalidations.
+ * Mount once, near the root of the app.
+ */
+export function useTmuxEvents() {
+  const queryClient = useQueryClient();
+  const live = useTmuxControl();
+
+  useEffect(() => {
+    if (!live) return;
+
+    // A busy agent emits output continuously; refresh captures at most once
+    // per throttle window rather than per event
+    const dirty = new Set<string>();
+    let timer: ReturnType<typeof setTimeout> | undefined;
+    const flushOutput = () => {
+      timer = undefined;
+      for (const session of dirty) {
+        queryClient.invalidateQueries({
+          queryKey: ["tmux", "capture", session],
+        });
+      }
+      dirty.clear();
+      queryClient.invalidateQueries({ queryKey: ["tmux", "claude-states"] });
+    };
+
+    const invalidateSessions = () =>
+      queryClient.invalidateQueries({ queryKey: ["tmux", "sessions"] });
+    const unlisteners = [
+      listen("tmux://sessions-changed", invalidateSessions),
+      listen("tmux://window-add", invalidateSessions),
+      listen("tmux://exit", invalidateSessions),
+      listen<TmuxOutputEvent>("tmux://output", ({ payload }) => {
+        dirty.add(payload.session);
+        timer ??= setTimeout(flushOutput, OUTPUT_REFRESH_THROTTLE);
+      }),
+    ];
+
+    return () => {
+      if (timer) clearTimeout(timer);
+      for (const unlisten of unlisteners) {
+        unlisten.then((fn) => fn());
+      }
+    };
+  }, [live, queryClient]);
+}
+
+/** Keep the hook-reported agent states current. Mount once, near the root. */
+export function useAgentHookEvents() {
+  const queryClient = useQueryClient();
+
+  useEffect(() => {
+    const unlisten = listen<TaskAgentState>("agent://state", ({ payload }) => {
+      queryClient.setQueryData<TaskAgentState[]>(["agent", "states"], (old) => [
+        ...(old ?? []).filter((s) => s.worktree !== payload.worktree),
+        payload,
+      ]);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [queryClient]);
+}
+
+/** Toast the sessions the reaper reports idle. Mount once, near the root. */
+export function useIdleSessionEvents() {
+  useEffect(() => {
+    const unlisten = listen<ReapDecision>("session://idle", ({ payload }) => {
+      toast.info(`${payload.issue ?? payload.session} is idle`, {
+        description: payload.reason,
+      });
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+}
+
+/**
+ * Recreate the sessions lost since the last run (e.g. to a reboot).
+ * Runs once per app launch.
+ */
+export function useTmuxRestore() {
+  const queryClient = useQueryClient();
+  const ran = useRef(false);
+
+  useEffect(() => {
+    if (ran.current) return;
+    ran.current = true;
+
+    tmuxRestoreSessions()
+      .then((results) => {
+        const restored = results.filter((r) => r.restored);
+        if (restored.length > 0) {
+          const resumed = restored.filter((r) => r.resumed).length;
+          toast.success(
+            `Restored ${restored.length} session(s), ${resumed} resuming their agent`,
+          );
+          queryClient.invalidateQueries({ queryKey: ["tmux", "sessions"] });
+        }
+        for (const r of results.filter((r) => !r.restored)) {
+          toast.warning(`Session ${r.name} not restored: ${r.message}`);
+        }
+      })
+      .catch((err) => {
+        toast.warning(
+          `Failed to restore sessions: ${err instanceof Error ? err.message : String(err)}`,
+        );
+      });
+  }, [queryClient]);
+}
+
+/** Task sessions on the default tmux server, and what moving them would do. */
+export function useTmuxAdoptionPreview() {
+  return useQuery<AdoptedSession[]>({
+    queryKey: ["tmux", "adoption"],
+    // Fails when no dedicated socket is configured; nothing to adopt then
+    queryFn: () => tmuxAdoptSessions().catch(() => []),
+  });
+}
+
+/** Move the given sessions onto directiv's server. */
+export function useTmuxAdoptSessions() {
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: (names: string[]) => tmuxAdoptSessions(names, false),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["tmux", "sessions"] });
+      queryClient.invalidateQueries({ queryKey: ["tmux", "adoption"] });
+    },
+  });
+}
 
 export function useTmuxSessions() {
+  const live = useTmuxControl();
   return useQuery<TmuxSession[]>({
     queryKey: ["tmux", "sessions"],
     queryFn: tmuxListSessions,
-    refetchInterval: LOCAL_REFRESH_INTERVAL,
+    refetchInterval: live ? false : LOCAL_REFRESH_INTERVAL,
   });
 }
 
@@ -38,44 +205,84 @@ export function useTmuxKillSession() {
 }
 
 export function useTmuxCapturePane(session: string | undefined) {
+  const live = useTmuxControl();
   return useQuery<string>({
     queryKey: ["tmux", "capture", session],
     queryFn: () => tmuxCapturePane(session!),
     enabled: !!session,
-    refetchInterval: LOCAL_REFRESH_INTERVAL,
+    refetchInterval: live ? false : LOCAL_REFRESH_INTERVAL,
   });
 }
 
-export function useClaudeSessionStates(sessionNames: string[]) {
-  const previousRef = useRef<Map<string, string> | null>(null);
+/** Colored tail of the agent's pane, including recent scrollback. */
+export function useTmuxPanePreview(session: TmuxSession | null, lines = 40) {
+  const live = useTmuxControl();
+  return useQuery<StyledSpan[][]>({
+    // Under ["tmux", "capture", name] so output events refresh it
+    queryKey: ["tmux", "capture", session?.name, "spans", lines],
+    queryFn: () =>
+      tmuxCaptureSpans(session!.name, session!.agentPane ?? undefined, {
+        start: String(-lines),
+        join: true,
+      }),
+    enabled: !!session,
+    refetchInterval: live ? false : LOCAL_REFRESH_INTERVAL,
+  });
+}
 
-  const sorted = [...sessionNames].sort();
-  const key = sorted.join(",");
+const HOOK_STATUS: Record<HookState, ClaudeSessionStatus> = {
+  working: "working",
+  waitingPermission: "permission",
+  idle: "idle",
+  finished: "idle",
+};
 
-  return useQuery<Map<string, ClaudeSessionStatus>>({
-    queryKey: ["tmux", "claude-states", key],
+/**
+ * Agent state per session name. States reported by the agent's hooks win over
+ * the ones the backend detects from the agent pane.
+ */
+export function useClaudeSessionStates(sessions: TmuxSession[]) {
+  const live = useTmuxControl();
+  const names = sessions.map((s) => s.name).sort();
+  const { data: hookStates } = useQuery<TaskAgentState[]>({
+    queryKey: ["agent", "states"],
+    queryFn: agentStates,
+    staleTime: Infinity,
+  });
+
+  const detected = useQuery<Map<string, ClaudeSessionStatus>>({
+    queryKey: ["tmux", "claude-states", names.join(",")],
     queryFn: async () => {
-      const entries = await Promise.all(
-        sorted.map(async (name) => {
-          try {
-            const content = await tmuxCapturePane(name);
-            return [name, content] as const;
-          } catch {
-            return null;
-          }
-        }),
-      );
-
-      const current = new Map<string, string>();
-      for (const entry of entries) {
-        if (entry) current.set(entry[0], entry[1]);
-      }
+      const states = await tmuxAgentStates(names);
+      return new Map(states.map((s) => [s.session, s.state]));
+    },
+    enabled: names.length > 0,
+    // Output events drive refreshes; the slow poll is a fallback
+    refetchInterval: live
+      ? LOCAL_REFRESH_INTERVAL_SLOW
+      : LOCAL_REFRESH_INTERVAL,
+  });
 
-      const states = detectClaudeStates(current, previousRef.current);
-      previousRef.current = current;
-      return states;
+  const data = useMemo(() => {
+    const byWorktree = new Map(hookStates?.map((s) => [s.worktree, s.state]));
+    const merged = new Map(detected.data);
+    for (const session of sessions) {
+      const hookState = session.worktree && byWorktree.get(session.worktree);
+      if (hookState) merged.set(session.name, HOOK_STATUS[hookState]);
+    }
+    return merged;
+  }, [detected.data, hookStates, sessions]);
+  return { ...detected, data };
+}
+
+/** Resource use per session name, polled while the board is open. */
+export function useSessionResources() {
+  return useQuery<Map<string, SessionResources>>({
+    queryKey: ["tmux", "resources"],
+    queryFn: async () => {
+      const resources = await tmuxSessionResources();
+      return new Map(resources.map((r) => [r.session, r]));
     },
-    enabled: sorted.length > 0,
     refetchInterval: LOCAL_REFRESH_INTERVAL,
   });
 }
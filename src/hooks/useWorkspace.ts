@@ -8,6 +8,7 @@ export function useWorkspaceInit() {
   const isConfigLoaded = useSettingsStore((s) => s.isLoaded);
   const isHydrated = useWorkspaceStore((s) => s.isHydrated);
   const scanAllWorkspaces = useWorkspaceStore((s) => s.scanAllWorkspaces);
+  const repairWorkspaces = useWorkspaceStore((s) => s.repairWorkspaces);
   const hasScanned = useRef(false);
 
   useEffect(() => {
@@ -19,8 +20,16 @@ export function useWorkspaceInit() {
     ) {
       hasScanned.current = true;
       scanAllWorkspaces(workspaces);
+      // Once per launch; Settings → Maintenance runs it again on demand
+      repairWorkspaces(workspaces, true);
     }
-  }, [isConfigLoaded, isHydrated, workspaces, scanAllWorkspaces]);
+  }, [
+    isConfigLoaded,
+    isHydrated,
+    workspaces,
+    scanAllWorkspaces,
+    repairWorkspaces,
+  ]);
 }
 
 export function useActiveWorkspace(): WorkspaceConfig | null {
@@ -1,7 +1,30 @@
-import { invoke } from "@tauri-apps/api/core";
+import { Channel, invoke } from "@tauri-apps/api/core";
 import type {
   TmuxSession,
+  SessionAgentState,
+  TaskAgentState,
+  AdoptedSession,
+  RestoredSession,
+  BroadcastFilter,
+  BroadcastOptions,
+  BroadcastResult,
+  BroadcastRule,
+  CaptureBatch,
+  CaptureOptions,
+  StyledSpan,
+  LogFile,
+  LogPage,
+  ReadyOptions,
+  ReadyReport,
+  Recording,
+  SessionResources,
+  SearchOptions,
+  SearchEvent,
   WorktreeInfo,
+  RestackResult,
+  GcDecision,
+  ReapDecision,
+  RepairReport,
   PluginSkillInfo,
   DiscoveredRepo,
 } from "../types";
@@ -14,6 +37,7 @@ interface RawDiscoveredRepo {
   copyPaths: string[];
   onStart: string[];
   fetchBefore: boolean;
+  poolSize: number;
   configWarning?: string;
 }
 
@@ -42,6 +66,7 @@ export function worktreeCreate(
   copyPaths?: string[],
   baseBranch?: string,
   fetchBefore?: boolean,
+  parentBranch?: string,
 ): Promise<WorktreeInfo> {
   return invoke<WorktreeInfo>("worktree_create", {
     repoPath,
@@ -49,6 +74,7 @@ export function worktreeCreate(
     copyPaths,
     baseBranch,
     fetchBefore,
+    parentBranch,
   });
 }
 
@@ -80,36 +106,317 @@ export function gitFetchPrune(repoPath: string): Promise<void> {
   return invoke<void>("git_fetch_prune", { repoPath });
 }
 
+export function worktreeRestack(
+  repoPath: string,
+  branch: string,
+): Promise<RestackResult[]> {
+  return invoke<RestackResult[]>("worktree_restack", { repoPath, branch });
+}
+
+export function worktreeGcRun(preview = true): Promise<GcDecision[]> {
+  return invoke<GcDecision[]>("worktree_gc_run", { preview });
+}
+
+export function worktreeGcLog(limit?: number): Promise<GcDecision[]> {
+  return invoke<GcDecision[]>("worktree_gc_log", { limit });
+}
+
+/** Idle sessions and what the reaper does with them; a preview by default. */
+export function sessionReaperRun(preview = true): Promise<ReapDecision[]> {
+  return invoke<ReapDecision[]>("session_reaper_run", { preview });
+}
+
+export function sessionReaperLog(limit?: number): Promise<ReapDecision[]> {
+  return invoke<ReapDecision[]>("session_reaper_log", { limit });
+}
+
+/** The web view's URL with its token, `null` when it isn't enabled. */
+export function webViewUrl(): Promise<string | null> {
+  return invoke<string | null>("web_view_url");
+}
+
+export function worktreeRepair(repoPath: string): Promise<RepairReport> {
+  return invoke<RepairReport>("worktree_repair", { repoPath });
+}
+
+export function workspaceRepair(
+  workspacePath: string,
+): Promise<RepairReport[]> {
+  return invoke<RepairReport[]>("workspace_repair", { workspacePath });
+}
+
 // --- Tmux commands ---
 
 export function tmuxListSessions(): Promise<TmuxSession[]> {
   return invoke<TmuxSession[]>("tmux_list_sessions");
 }
 
+export interface SessionMetadata {
+  issue?: string;
+  repo?: string;
+  agent?: string;
+  // Overrides `logs.enabled` from the config
+  logOutput?: boolean;
+  issueUrl?: string;
+  // Defaults to the repo's default branch
+  baseBranch?: string;
+  // Extra environment variables, on top of the repo's `env`
+  env?: Record<string, string>;
+}
+
+/** The created session's name may carry a suffix if `name` was taken. */
 export function tmuxCreateSession(
   name: string,
   workingDir?: string,
+  metadata?: SessionMetadata,
 ): Promise<TmuxSession> {
-  return invoke<TmuxSession>("tmux_create_session", { name, workingDir });
+  return invoke<TmuxSession>("tmux_create_session", {
+    name,
+    workingDir,
+    issue: metadata?.issue,
+    repo: metadata?.repo,
+    agent: metadata?.agent,
+    logOutput: metadata?.logOutput,
+    env: {
+      issueUrl: metadata?.issueUrl,
+      baseBranch: metadata?.baseBranch,
+      vars: metadata?.env,
+    },
+  });
 }
 
 export function tmuxKillSession(name: string): Promise<void> {
   return invoke<void>("tmux_kill_session", { name });
 }
 
-export function tmuxSendKeys(session: string, keys: string): Promise<void> {
-  return invoke<void>("tmux_send_keys", { session, keys });
+/** `pane` is a pane id or the name of a pane from the repo's layout. */
+export function tmuxSendKeys(
+  session: string,
+  keys: string,
+  pane?: string,
+): Promise<void> {
+  return invoke<void>("tmux_send_keys", { session, keys, pane });
 }
 
-export function tmuxCapturePane(session: string): Promise<string> {
-  return invoke<string>("tmux_capture_pane", { session });
+/**
+ * Type the agent's start command into its pane (the agent pane by default) and
+ * remember it for when the session is restored.
+ */
+export function tmuxStartAgent(
+  session: string,
+  command: string,
+  pane?: string,
+): Promise<void> {
+  return invoke<void>("tmux_start_agent", { session, command, pane });
 }
 
+/**
+ * Paste multi-line text as a single bracketed paste, optionally pressing Enter
+ * after it.
+ */
+export function tmuxPaste(
+  session: string,
+  text: string,
+  options?: { pane?: string; submit?: boolean },
+): Promise<void> {
+  return invoke<void>("tmux_paste", {
+    session,
+    text,
+    pane: options?.pane,
+    submit: options?.submit,
+  });
+}
+
+/** Only reports what would happen unless `preview` is false. */
+export function tmuxAdoptSessions(
+  names?: string[],
+  preview = true,
+): Promise<AdoptedSession[]> {
+  return invoke<AdoptedSession[]>("tmux_adopt_sessions", { names, preview });
+}
+
+/**
+ * Send `text` to the agent of every session matching `filter`. Sessions whose
+ * rule is "confirm" only receive it once listed in `options.confirmed`.
+ */
+export function tmuxBroadcast(
+  text: string,
+  filter?: BroadcastFilter,
+  options?: BroadcastOptions,
+): Promise<BroadcastResult[]> {
+  return invoke<BroadcastResult[]>("tmux_broadcast", { text, filter, options });
+}
+
+export function tmuxSetLabels(
+  session: string,
+  labels: string[],
+): Promise<void> {
+  return invoke<void>("tmux_set_labels", { session, labels });
+}
+
+export function tmuxSetBroadcastRule(
+  session: string,
+  rule: BroadcastRule,
+): Promise<void> {
+  return invoke<void>("tmux_set_broadcast_rule", { session, rule });
+}
+
+/** Recreate the task sessions saved before the tmux server went away. */
+export function tmuxRestoreSessions(): Promise<RestoredSession[]> {
+  return invoke<RestoredSession[]>("tmux_restore_sessions");
+}
+
+export function tmuxControlStart(): Promise<boolean> {
+  return invoke<boolean>("tmux_control_start");
+}
+
+export function tmuxControlStop(): Promise<void> {
+  return invoke<void>("tmux_control_stop");
+}
+
+export function tmuxCapturePane(
+  session: string,
+  pane?: string,
+  options?: CaptureOptions,
+): Promise<string> {
+  return invoke<string>("tmux_capture_pane", { session, pane, options });
+}
+
+export function tmuxCaptureSpans(
+  session: string,
+  pane?: string,
+  options?: CaptureOptions,
+): Promise<StyledSpan[][]> {
+  return invoke<StyledSpan[][]>("tmux_capture_spans", {
+    session,
+    pane,
+    options,
+  });
+}
+
+/**
+ * Every directiv session's pane in one tmux call; content only for the panes
+ * that changed after batch `since`.
+ */
+export function tmuxCaptureAll(
+  since?: number,
+  options?: CaptureOptions,
+): Promise<CaptureBatch> {
+  return invoke<CaptureBatch>("tmux_capture_all", { since, options });
+}
+
+/** Agent state of `sessions` (every session when omitted). */
+export function tmuxAgentStates(
+  sessions?: string[],
+): Promise<SessionAgentState[]> {
+  return invoke<SessionAgentState[]>("tmux_agent_states", { sessions });
+}
+
+/** Resolves with `ready: false` and a diagnostic when a stage fails. */
 export function tmuxWaitForReady(
   session: string,
-  timeoutMs?: number,
+  options?: ReadyOptions,
+): Promise<ReadyReport> {
+  return invoke<ReadyReport>("tmux_wait_for_ready", { session, options });
+}
+
+/** CPU, memory and processes of every task session; takes about 500ms. */
+export function tmuxSessionResources(): Promise<SessionResources[]> {
+  return invoke<SessionResources[]>("tmux_session_resources");
+}
+
+// --- Session logs ---
+
+/** `task` is the issue identifier, or the branch of a free task. */
+export function taskLogsList(
+  repoPath: string,
+  task: string,
+): Promise<LogFile[]> {
+  return invoke<LogFile[]>("task_logs_list", { repoPath, task });
+}
+
+export function taskLogRead(
+  repoPath: string,
+  task: string,
+  name: string,
+  offset?: number,
+  limit?: number,
+): Promise<LogPage> {
+  return invoke<LogPage>("task_log_read", {
+    repoPath,
+    task,
+    name,
+    offset,
+    limit,
+  });
+}
+
+export function taskLogsDelete(
+  repoPath: string,
+  task: string,
+  name?: string,
+): Promise<number> {
+  return invoke<number>("task_logs_delete", { repoPath, task, name });
+}
+
+export function taskRecordingsList(
+  repoPath: string,
+  task: string,
+): Promise<Recording[]> {
+  return invoke<Recording[]>("task_recordings_list", { repoPath, task });
+}
+
+/** Writes the segment to the task's `exports` directory by default. */
+export function taskRecordingExport(
+  repoPath: string,
+  task: string,
+  name: string,
+  start: number,
+  end?: number,
+  destination?: string,
+): Promise<string> {
+  return invoke<string>("task_recording_export", {
+    repoPath,
+    task,
+    name,
+    start,
+    end,
+    destination,
+  });
+}
+
+export function tmuxSetRecording(
+  session: string,
+  record: boolean,
 ): Promise<void> {
-  return invoke<void>("tmux_wait_for_ready", { session, timeoutMs });
+  return invoke<void>("tmux_set_recording", { session, record });
+}
+
+// --- Search ---
+
+/** Resolves once the search is done; matches arrive through `onEvent` first. */
+export function sessionSearch(
+  query: string,
+  onEvent: (event: SearchEvent) => void,
+  options?: SearchOptions,
+): Promise<void> {
+  const channel = new Channel<SearchEvent>();
+  channel.onmessage = onEvent;
+  return invoke<void>("session_search", {
+    query,
+    options,
+    onEvent: channel,
+  });
+}
+
+// --- Agent hooks ---
+
+export function agentHooksInstall(worktreePath: string): Promise<void> {
+  return invoke<void>("agent_hooks_install", { worktreePath });
+}
+
+export function agentStates(): Promise<TaskAgentState[]> {
+  return invoke<TaskAgentState[]>("agent_states");
 }
 
 // --- Hook commands ---
@@ -127,6 +434,32 @@ export function openTerminal(emulator: string, session: string): Promise<void> {
   return invoke<void>("open_terminal", { emulator, session });
 }
 
+/** Attach `session` in an embedded terminal; returns the terminal's id. */
+export function ptyOpen(
+  session: string,
+  cols: number,
+  rows: number,
+): Promise<number> {
+  return invoke<number>("pty_open", { session, cols, rows });
+}
+
+export function ptyWrite(id: number, data: string): Promise<void> {
+  return invoke<void>("pty_write", { id, data });
+}
+
+export function ptyResize(
+  id: number,
+  cols: number,
+  rows: number,
+): Promise<void> {
+  return invoke<void>("pty_resize", { id, cols, rows });
+}
+
+/** Detach the terminal; the tmux session keeps running. */
+export function ptyClose(id: number): Promise<void> {
+  return invoke<void>("pty_close", { id });
+}
+
 // --- Editor commands ---
 
 export function openEditor(editor: string, path: string): Promise<void> {
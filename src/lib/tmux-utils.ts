@@ -1,4 +1,24 @@
+import type { TmuxSession } from "../types";
+
 /** Sanitize a branch name / identifier into a valid tmux session name. */
 export function toSessionName(name: string): string {
   return name.replace(/[^a-zA-Z0-9_-]/g, "-");
 }
+
+/**
+ * Find the session of a task's worktree. Sessions tagged by directiv match on
+ * their worktree path; older untagged sessions fall back to the sanitized name.
+ */
+export function findTaskSession(
+  sessions: TmuxSession[],
+  branch: string,
+  worktreePath?: string | null,
+): TmuxSession | undefined {
+  const name = toSessionName(branch);
+  return (
+    (worktreePath
+      ? sessions.find((s) => s.worktree === worktreePath)
+      : undefined) ??
+    sessions.find((s) => s.worktree === null && s.name === name)
+  );
+}
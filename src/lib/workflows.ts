@@ -7,13 +7,14 @@ import {
   tmuxCreateSession,
   tmuxKillSession,
   tmuxListSessions,
-  tmuxSendKeys,
+  tmuxStartAgent,
   tmuxWaitForReady,
   openTerminal,
   runHooks,
   getPluginDir,
 } from "./tauri";
-import { toSessionName } from "./tmux-utils";
+import { findTaskSession, toSessionName } from "./tmux-utils";
+import type { ReadyOptions } from "../types";
 
 export const SKILLS = {
   CODE: "directiv:linear-issue",
@@ -22,6 +23,29 @@ export const SKILLS = {
 
 export type Skill = (typeof SKILLS)[keyof typeof SKILLS];
 
+/** Throw with the failed stage's diagnostic if the session isn't ready. */
+async function waitForReady(
+  session: string,
+  options?: ReadyOptions,
+): Promise<void> {
+  const { ready, diagnostic } = await tmuxWaitForReady(session, options);
+  if (ready || !diagnostic) return;
+  const details = [
+    diagnostic.paneCommand && `Pane runs: ${diagnostic.paneCommand}`,
+    ...diagnostic.tail.slice(-5),
+  ].filter(Boolean);
+  throw new Error([diagnostic.reason, ...details].join("\n"));
+}
+
+/** Warn, without failing the start, if the agent doesn't come up. */
+function watchAgentStart(session: string) {
+  waitForReady(session, { stages: ["process", "prompt"] }).catch((err) => {
+    toast.warning(
+      `Agent did not start in ${session}: ${err instanceof Error ? err.message : String(err)}`,
+    );
+  });
+}
+
 async function buildClaudeCommand(
   skill?: string,
   identifier?: string,
@@ -46,6 +70,7 @@ async function buildClaudeCommand(
 export interface StartTaskParams {
   issueId: string;
   identifier: string;
+  issueUrl?: string;
   repoPath: string;
   terminal: string;
   copyPaths?: string[];
@@ -53,11 +78,14 @@ export interface StartTaskParams {
   baseBranch?: string;
   fetchBefore?: boolean;
   skill?: string;
+  /** Identifiers of blocking tasks; the first one with a worktree becomes the stack parent */
+  blockedBy?: string[];
 }
 
 export async function startTask({
   issueId,
   identifier,
+  issueUrl,
   repoPath,
   terminal,
   copyPaths,
@@ -65,35 +93,56 @@ export async function startTask({
   baseBranch,
   fetchBefore,
   skill,
+  blockedBy,
 }: StartTaskParams): Promise<void> {
-  // 1. Reuse or create git worktree
+  // 1. Reuse or create git worktree (stacked on a blocker's branch when one is in progress)
   const worktrees = await worktreeList(repoPath);
   let worktree = worktrees.find((w) => w.branch === identifier);
   if (!worktree) {
+    const parentBranch = blockedBy?.find((id) =>
+      worktrees.some((w) => w.branch === id),
+    );
     worktree = await worktreeCreate(
       repoPath,
       identifier,
       copyPaths,
       baseBranch,
       fetchBefore,
+      parentBranch,
     );
   }
 
   // 2. Reuse or create tmux session (with rollback on failure)
-  const sessionName = toSessionName(identifier);
   const sessions = await tmuxListSessions();
-  const existingSession = sessions.find((s) => s.name === sessionName);
-  if (!existingSession) {
-    // Run onStart hooks BEFORE tmux session (e.g. mise trust)
-    if (onStart && onStart.length > 0) {
+  let sessionName = findTaskSession(sessions, identifier, worktree.path)?.name;
+  if (!sessionName) {
+    // Run onStart hooks BEFORE tmux session (e.g. mise trust); a pooled
+    // worktree already ran them
+    if (onStart && onStart.length > 0 && !worktree.fromPool) {
       await runHooks(onStart, worktree.path);
     }
-    await tmuxCreateSession(sessionName, worktree.path);
+    const session = await tmuxCreateSession(
+      toSessionName(identifier),
+      worktree.path,
+      {
+        issue: identifier,
+        repo: repoPath,
+        agent: "claude",
+        issueUrl,
+        baseBranch: worktree.parentBranch ?? baseBranch,
+      },
+    );
+    sessionName = session.name;
     try {
-      await tmuxWaitForReady(sessionName);
-      // 3. Launch Claude only on fresh sessions
+      await waitForReady(sessionName);
+      // 3. Launch Claude only on fresh sessions, in the layout's agent pane
       const claudeCmd = await buildClaudeCommand(skill, identifier);
-      await tmuxSendKeys(sessionName, claudeCmd);
+      await tmuxStartAgent(
+        sessionName,
+        claudeCmd,
+        session.agentPane ?? undefined,
+      );
+      watchAgentStart(sessionName);
     } catch (err) {
       // Rollback: kill session so retry creates a fresh one
       await tmuxKillSession(sessionName).catch(() => {});
@@ -149,20 +198,30 @@ export async function startFreeTask({
   }
 
   // 2. Reuse or create tmux session (with rollback on failure)
-  const sessionName = toSessionName(branchName);
   const sessions = await tmuxListSessions();
-  const existingSession = sessions.find((s) => s.name === sessionName);
-  if (!existingSession) {
-    // Run onStart hooks BEFORE tmux session (e.g. mise trust)
-    if (onStart && onStart.length > 0) {
+  let sessionName = findTaskSession(sessions, branchName, worktree.path)?.name;
+  if (!sessionName) {
+    // Run onStart hooks BEFORE tmux session (e.g. mise trust); a pooled
+    // worktree already ran them
+    if (onStart && onStart.length > 0 && !worktree.fromPool) {
       await runHooks(onStart, worktree.path);
     }
-    await tmuxCreateSession(sessionName, worktree.path);
+    const session = await tmuxCreateSession(
+      toSessionName(branchName),
+      worktree.path,
+      { repo: repoPath, agent: "claude", baseBranch },
+    );
+    sessionName = session.name;
     try {
-      await tmuxWaitForReady(sessionName);
+      await waitForReady(sessionName);
       // 3. Launch Claude (plain, no /linear-issue)
       const claudeCmd = await buildClaudeCommand();
-      await tmuxSendKeys(sessionName, claudeCmd);
+      await tmuxStartAgent(
+        sessionName,
+        claudeCmd,
+        session.agentPane ?? undefined,
+      );
+      watchAgentStart(sessionName);
     } catch (err) {
       // Rollback: kill session so retry creates a fresh one
       await tmuxKillSession(sessionName).catch(() => {});
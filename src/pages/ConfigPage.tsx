@@ -1,9 +1,16 @@
 import { useState } from "react";
 import { Link } from "@tanstack/react-router";
-import { ArrowLeft, Sparkles, Settings2, FolderGit2 } from "lucide-react";
+import {
+  ArrowLeft,
+  Sparkles,
+  Settings2,
+  FolderGit2,
+  Wrench,
+} from "lucide-react";
 import { SkillsSection } from "../components/Config/SkillsSection";
+import { MaintenanceSection } from "../components/Config/MaintenanceSection";
 
-type ConfigSection = "skills" | "general" | "repositories";
+type ConfigSection = "skills" | "general" | "repositories" | "maintenance";
 
 export function ConfigPage() {
   const [activeSection, setActiveSection] = useState<ConfigSection>("skills");
@@ -40,6 +47,12 @@ export function ConfigPage() {
             active={activeSection === "repositories"}
             onClick={() => setActiveSection("repositories")}
           />
+          <MenuItem
+            icon={<Wrench className="size-4" />}
+            label="Maintenance"
+            active={activeSection === "maintenance"}
+            onClick={() => setActiveSection("maintenance")}
+          />
         </nav>
       </aside>
 
@@ -52,6 +65,7 @@ export function ConfigPage() {
         {activeSection === "repositories" && (
           <PlaceholderSection title="Repositories" />
         )}
+        {activeSection === "maintenance" && <MaintenanceSection />}
       </main>
     </div>
   );
@@ -1,17 +1,45 @@
 import { create } from "zustand";
 import { persist } from "zustand/middleware";
-import type { WorkspaceConfig, DiscoveredRepo } from "../types";
-import { scanWorkspace } from "../lib/tauri";
+import { toast } from "sonner";
+import type { WorkspaceConfig, DiscoveredRepo, RepairReport } from "../types";
+import { scanWorkspace, workspaceRepair } from "../lib/tauri";
+
+/** One toast summing up the repairs; `quiet` skips it when none were needed. */
+function notifyRepairs(reports: RepairReport[], quiet: boolean) {
+  const fixed = reports.reduce((n, r) => n + r.fixed.length, 0);
+  const unresolved = reports.flatMap((r) => r.unresolved);
+  if (unresolved.length > 0) {
+    toast.warning(
+      `Repaired ${fixed} moved worktree(s), ${unresolved.length} need attention`,
+      {
+        description: unresolved
+          .map((u) => `${u.branch || u.path}: ${u.reason}`)
+          .join("\n"),
+      },
+    );
+  } else if (fixed > 0) {
+    toast.success(`Repaired ${fixed} moved worktree(s)`);
+  } else if (!quiet) {
+    toast.success("No moved worktrees found");
+  }
+}
 
 interface WorkspaceState {
   activeWorkspaceId: string | null;
   repos: DiscoveredRepo[];
   isScanning: boolean;
+  isRepairing: boolean;
   isHydrated: boolean;
   error: string | null;
 
   setActiveWorkspace: (id: string) => void;
   scanAllWorkspaces: (workspaces: WorkspaceConfig[]) => Promise<void>;
+  // Reconnect worktrees after a workspace or repo was moved; `quiet` toasts
+  // only when something was repaired or needs attention
+  repairWorkspaces: (
+    workspaces: WorkspaceConfig[],
+    quiet?: boolean,
+  ) => Promise<void>;
   setHydrated: () => void;
 }
 
@@ -21,6 +49,7 @@ export const useWorkspaceStore = create<WorkspaceState>()(
       activeWorkspaceId: null,
       repos: [],
       isScanning: false,
+      isRepairing: false,
       isHydrated: false,
       error: null,
 
@@ -70,6 +99,22 @@ export const useWorkspaceStore = create<WorkspaceState>()(
         }
       },
 
+      repairWorkspaces: async (workspaces, quiet = false) => {
+        if (get().isRepairing) return;
+        set({ isRepairing: true });
+
+        const reports: RepairReport[] = [];
+        for (const ws of workspaces) {
+          try {
+            reports.push(...(await workspaceRepair(ws.path)));
+          } catch (err) {
+            console.warn(`Failed to repair workspace ${ws.id}:`, err);
+          }
+        }
+        set({ isRepairing: false });
+        notifyRepairs(reports, quiet);
+      },
+
       setHydrated: () => set({ isHydrated: true }),
     }),
     {
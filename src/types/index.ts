@@ -65,7 +65,40 @@ export interface EnrichedTask {
 
 // --- Claude Session ---
 
-export type ClaudeSessionStatus = "active" | "waiting" | "unknown";
+export type ClaudeSessionStatus =
+  | "working"
+  | "permission"
+  | "error"
+  | "idle"
+  | "unknown";
+
+export interface AgentEvidence {
+  // Name of the pattern that matched
+  rule: string;
+  line: number;
+  text: string;
+}
+
+// Reported by the Claude Code hooks directiv installs in each worktree
+export type HookState = "working" | "waitingPermission" | "idle" | "finished";
+
+export interface TaskAgentState {
+  worktree: string;
+  sessionId: string | null;
+  state: HookState;
+  event: string;
+  // Tool name or notification message
+  detail: string | null;
+  // Unix millis
+  since: number;
+  updated: number;
+}
+
+export interface SessionAgentState {
+  session: string;
+  state: ClaudeSessionStatus;
+  evidence: AgentEvidence | null;
+}
 
 // --- Tmux ---
 
@@ -74,6 +107,237 @@ export interface TmuxSession {
   attached: boolean;
   windows: number;
   created: string;
+  // Task metadata; null on sessions not created by directiv
+  issue: string | null;
+  repo: string | null;
+  worktree: string | null;
+  agent: string | null;
+  // Pane id (`%N`) the agent runs in
+  agentPane: string | null;
+}
+
+export interface AdoptedSession {
+  name: string;
+  worktree: string | null;
+  repo: string | null;
+  // A single pane at a shell prompt, inside a task worktree
+  movable: boolean;
+  adopted: boolean;
+  message: string;
+}
+
+export interface RestoredSession {
+  name: string;
+  restored: boolean;
+  // The agent continues its previous conversation
+  resumed: boolean;
+  message: string;
+}
+
+export type BroadcastRule = "allow" | "confirm" | "deny";
+
+// Every field narrows the selection; an empty one matches everything
+export interface BroadcastFilter {
+  sessions?: string[];
+  repos?: string[];
+  states?: ClaudeSessionStatus[];
+  labels?: string[];
+}
+
+export interface BroadcastOptions {
+  // Bracketed paste, for multi-line text
+  paste?: boolean;
+  noSubmit?: boolean;
+  dryRun?: boolean;
+  // Sessions with the "confirm" rule the user agreed to send to
+  confirmed?: string[];
+}
+
+export type DeliveryStatus =
+  | "delivered"
+  | "matched"
+  | "needsConfirmation"
+  | "refused"
+  | "failed";
+
+export interface BroadcastResult {
+  session: string;
+  issue: string | null;
+  status: DeliveryStatus;
+  error: string | null;
+}
+
+// --- Session logs ---
+
+export interface LogFile {
+  name: string;
+  size: number;
+  modified: number; // Unix seconds
+}
+
+export interface LogPage {
+  lines: string[];
+  offset: number;
+  totalLines: number;
+  nextOffset: number | null;
+}
+
+export interface Recording extends LogFile {
+  duration: number | null; // Seconds up to the last recorded output
+}
+
+export type ReadyStage = "shell" | "process" | "prompt";
+
+export interface ReadyOptions {
+  // Default ["shell"]
+  stages?: ReadyStage[];
+  shellTimeoutMs?: number;
+  processTimeoutMs?: number;
+  promptTimeoutMs?: number;
+  // Default: the session's agent
+  processNames?: string[];
+  // Regex; default recognizes the agent's UI
+  promptPattern?: string;
+}
+
+export interface ReadyDiagnostic {
+  stage: ReadyStage;
+  reason: string;
+  elapsedMs: number;
+  paneCommand: string | null;
+  tail: string[];
+}
+
+export interface ReadyReport {
+  ready: boolean;
+  stages: { stage: ReadyStage; elapsedMs: number }[];
+  diagnostic: ReadyDiagnostic | null;
+}
+
+export interface ProcessUsage {
+  pid: number;
+  name: string;
+  cpuPercent: number;
+  rssKb: number;
+}
+
+export interface PaneResources {
+  pane: string;
+  windowIndex: number;
+  windowName: string;
+  command: string;
+  // Percent of one core; over 100 when several cores are busy
+  cpuPercent: number;
+  rssKb: number;
+  processes: number;
+  busiest: ProcessUsage | null;
+}
+
+export interface SessionResources {
+  session: string;
+  issue: string | null;
+  // Command in the agent pane
+  foreground: string | null;
+  cpuPercent: number;
+  rssKb: number;
+  processes: number;
+  panes: PaneResources[];
+}
+
+export interface SearchOptions {
+  regex?: boolean;
+  caseSensitive?: boolean;
+  // Lines of context around each match (default 2)
+  context?: number;
+  sessions?: boolean;
+  logs?: boolean;
+  maxResults?: number;
+}
+
+interface LineMatch {
+  lineNumber: number;
+  line: string;
+  before: string[];
+  after: string[];
+}
+
+export type SearchHit =
+  | (LineMatch & {
+      source: "session";
+      session: string;
+      issue: string | null;
+      windowIndex: number;
+      windowName: string;
+      pane: string;
+    })
+  | (LineMatch & { source: "log"; repo: string; task: string; log: string });
+
+export type SearchEvent =
+  | { event: "hit"; data: SearchHit }
+  | { event: "done"; data: { hits: number; truncated: boolean } };
+
+export interface CaptureOptions {
+  // First/last line (`-S`/`-E`); negative reaches into scrollback, "-" is the end
+  start?: string;
+  end?: string;
+  escapes?: boolean;
+  join?: boolean;
+  window?: string;
+}
+
+export interface PaneCapture {
+  session: string;
+  pane: string | null; // null for the session's active pane
+  hash: string;
+  changed: number; // Cursor of the batch the content last changed in
+  content: string | null; // Only when changed since the requested cursor
+}
+
+export interface CaptureBatch {
+  cursor: number;
+  panes: PaneCapture[];
+}
+
+// 0-15 are theme palette indices, anything else is resolved to #rrggbb
+export type AnsiColor = { palette: number } | { rgb: string };
+
+export interface StyledSpan {
+  text: string;
+  fg: AnsiColor | null;
+  bg: AnsiColor | null;
+  bold: boolean;
+  dim: boolean;
+  italic: boolean;
+  underline: boolean;
+  inverse: boolean;
+  strikethrough: boolean;
+}
+
+// Events emitted by the tmux control-mode clients
+export interface TmuxWindowAddEvent {
+  session: string;
+  windowId: string;
+}
+
+export interface TmuxOutputEvent {
+  session: string;
+  paneId: string;
+  data: string;
+}
+
+export interface TmuxExitEvent {
+  session: string;
+  reason: string | null;
+}
+
+export interface PtyOutputEvent {
+  id: number;
+  data: string;
+}
+
+export interface PtyExitEvent {
+  id: number;
+  code: number | null;
 }
 
 // --- Git Worktrees ---
@@ -85,6 +349,18 @@ export interface WorktreeInfo {
   isDirty: boolean;
   ahead: number;
   behind: number;
+  parentBranch: string | null;
+  isMissing: boolean;
+  fromPool: boolean; // Claimed pre-warmed, onStart hooks already ran
+}
+
+export type RestackStatus = "rebased" | "retargeted" | "skipped" | "failed";
+
+export interface RestackResult {
+  branch: string;
+  onto: string;
+  status: RestackStatus;
+  message: string | null;
 }
 
 // --- GitHub ---
@@ -149,6 +425,58 @@ export interface StaleWorktree {
   repoPath: string;
 }
 
+// --- Worktree GC ---
+
+export type GcAction = "keep" | "remove" | "archive";
+
+export interface GcDecision {
+  repoPath: string;
+  branch: string;
+  path: string;
+  action: GcAction;
+  reason: string;
+  idleDays: number;
+  applied: boolean;
+  error: string | null;
+  timestamp: number;
+}
+
+// --- Session reaper ---
+
+export type ReapAction = "keep" | "notify" | "detach" | "kill" | "archive";
+
+export interface ReapDecision {
+  session: string;
+  issue: string | null;
+  worktree: string | null;
+  action: ReapAction;
+  reason: string;
+  idleMinutes: number;
+  applied: boolean;
+  error: string | null;
+  timestamp: number;
+}
+
+// --- Worktree repair ---
+
+export interface RepairedWorktree {
+  branch: string;
+  oldPath: string;
+  newPath: string;
+}
+
+export interface UnresolvedWorktree {
+  branch: string;
+  path: string;
+  reason: string;
+}
+
+export interface RepairReport {
+  repoPath: string;
+  fixed: RepairedWorktree[];
+  unresolved: UnresolvedWorktree[];
+}
+
 // --- Skills ---
 
 export interface PluginSkillInfo {
@@ -176,6 +504,7 @@ export interface DiscoveredRepo {
   copyPaths: string[];
   onStart: string[];
   fetchBefore: boolean;
+  poolSize: number;
   configWarning?: string;
 }
 